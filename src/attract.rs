@@ -0,0 +1,464 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{CameraController, CameraFlight, CameraLimits, GalaxyCamera};
+use crate::cinematic::Cinematic;
+use crate::input::ActionState;
+use crate::resources::{ChangeSeed, GalaxyGenerated, GalaxySettings, Masterik, StarSelected};
+use crate::savegame::{SavedCamera, SessionLayers, UserLayers};
+
+//keeps the attract rolls apart from everything else rolled from the galaxy seed
+const ATTRACT_SALT: u64 = 0x4154_5452_4143_5421;
+//length of the cross-fade from one galaxy to the next
+const FADE_SECONDS: f32 = 1.5;
+//a snapshot that doesn't arrive by then is skipped and the new galaxy fades in from black
+const CAPTURE_TIMEOUT: f32 = 1.0;
+
+//saved with the preferences, off unless someone turns it on for a display
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttractSettings {
+    pub enabled: bool,
+    pub idle_seconds: f32,
+    //how long each galaxy stays on screen
+    pub galaxy_seconds: f32,
+}
+
+impl Default for AttractSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_seconds: 120.0,
+            galaxy_seconds: 40.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttractPhase {
+    Drifting,
+    //waiting for the screenshot of the outgoing galaxy
+    Capturing,
+    //the screenshot covers the screen while the next galaxy spawns behind it
+    Generating,
+    //the screenshot fades out over the new galaxy
+    FadingIn,
+}
+
+//what was on screen before, put back once the player returns
+struct AttractReturn {
+    galaxy: GalaxySettings,
+    camera: Option<SavedCamera>,
+    layers: UserLayers,
+}
+
+pub struct AttractRun {
+    pub phase: AttractPhase,
+    //seconds spent in the current phase
+    pub elapsed: f32,
+    rng: SmallRng,
+    saved: AttractReturn,
+    //filled from the render world once the screenshot has been read back
+    capture: Arc<Mutex<Option<Image>>>,
+    //whether the snapshot holds the outgoing galaxy, otherwise the fade is black
+    captured: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct AttractMode {
+    //seconds since the last input
+    pub idle: f32,
+    pub run: Option<AttractRun>,
+    //the player's galaxy, regenerated once the one being generated is done, then its layers put back
+    restore: Option<AttractReturn>,
+    regenerating: bool,
+    //reused for every screenshot so egui only ever knows one texture
+    snapshot: Option<Handle<Image>>,
+}
+
+impl AttractMode {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    //how opaque the outgoing galaxy is over the incoming one, 0 outside of the fades
+    pub fn fade(&self) -> f32 {
+        let Some(run) = &self.run else {
+            return 0.0;
+        };
+        match run.phase {
+            AttractPhase::Drifting | AttractPhase::Capturing => 0.0,
+            AttractPhase::Generating => 1.0,
+            AttractPhase::FadingIn => 1.0 - (run.elapsed / FADE_SECONDS).min(1.0),
+        }
+    }
+}
+
+//same ranges the settings panel allows, narrowed to galaxies that look good from afar and generate quickly
+fn random_settings(rng: &mut SmallRng) -> GalaxySettings {
+    GalaxySettings {
+        total_stars: rng.gen_range(10000..=60000),
+        gen_seed: rng.gen_range(1000..9000000000),
+        spiral_arm_count: rng.gen_range(1..=4),
+        o_class: true,
+        b_class: true,
+        a_class: true,
+        f_class: true,
+        g_class: true,
+        k_class: true,
+        m_class: true,
+        angle_mod: rng.gen_range(0.0004..=0.0012),
+        radius_mod: rng.gen_range(1200.0..=4000.0),
+        distance_mod: rng.gen_range(30.0..=120.0),
+    }
+}
+
+//one slow flight between two stars, zooming in or out at random
+fn start_drift(
+    rng: &mut SmallRng,
+    positions: &[(f32, f32, f32)],
+    limits: &CameraLimits,
+    duration: f32,
+    controller: &mut CameraController,
+) {
+    if positions.is_empty() {
+        return;
+    }
+    let mut random_star = || {
+        let (x, y, _) = positions[rng.gen_range(0..positions.len())];
+        Vec2::new(x, y)
+    };
+    let (from, to) = (random_star(), random_star());
+
+    let wide = limits.max_scale * 0.5;
+    let close = (limits.max_scale * 0.12).max(limits.min_scale);
+    let (from_scale, to_scale) = if rng.gen_bool(0.5) {
+        (wide, close)
+    } else {
+        (close, wide)
+    };
+
+    controller.flight = Some(CameraFlight {
+        from,
+        to,
+        from_scale,
+        to_scale,
+        elapsed: 0.0,
+        duration,
+    });
+    controller.target_scale = to_scale;
+    controller.zoom_focus = Vec2::ZERO;
+}
+
+//any key, button, pointer movement, touch or stick counts, and ends attract mode on the spot
+pub fn detect_user_input(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    touches: Res<Touches>,
+    actions: Option<Res<ActionState>>,
+    cinematic: Option<Res<Cinematic>>,
+    mut ev_motion: EventReader<MouseMotion>,
+    mut ev_wheel: EventReader<MouseWheel>,
+    mut attract: ResMut<AttractMode>,
+    mut query_camera: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let touched = keys.get_pressed().next().is_some()
+        || mouse_buttons.get_pressed().next().is_some()
+        || gamepad_buttons.get_pressed().next().is_some()
+        || touches.iter().next().is_some()
+        || actions.is_some_and(|actions| actions.pan != Vec2::ZERO || actions.zoom != 0.0)
+        || ev_motion.read().count() > 0
+        || ev_wheel.read().count() > 0;
+
+    //a camera path playing back on its own is not the player walking away
+    if !touched {
+        if cinematic.map_or(true, |cinematic| cinematic.playback.is_none()) {
+            attract.idle += time.delta_seconds();
+        }
+        return;
+    }
+
+    attract.idle = 0.0;
+    let Some(run) = attract.run.take() else {
+        return;
+    };
+
+    if let (Some(camera), Ok((mut transform, mut projection, mut controller))) =
+        (&run.saved.camera, query_camera.get_single_mut())
+    {
+        restore_camera(camera, &mut transform, &mut projection, &mut controller);
+    }
+    attract.restore = Some(run.saved);
+}
+
+fn restore_camera(
+    camera: &SavedCamera,
+    transform: &mut Transform,
+    projection: &mut OrthographicProjection,
+    controller: &mut CameraController,
+) {
+    transform.translation = camera.translation.extend(transform.translation.z);
+    projection.scale = camera.scale;
+    controller.target_scale = camera.scale;
+    controller.flight = None;
+}
+
+pub fn run_attract_mode(
+    time: Res<Time>,
+    settings: Res<AttractSettings>,
+    limits: Res<CameraLimits>,
+    mut masterok: ResMut<Masterik>,
+    mut attract: ResMut<AttractMode>,
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+    mut query_camera: Query<
+        (&Transform, &OrthographicProjection, &mut CameraController),
+        With<GalaxyCamera>,
+    >,
+    layers: SessionLayers,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let generated = ev_generated.read().count() > 0;
+    let Ok((transform, projection, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+    let attract = &mut *attract;
+
+    //puts back the galaxy from before, after any generation still in progress
+    if attract.run.is_none() {
+        if !masterok.block_input && !attract.regenerating {
+            match attract
+                .restore
+                .as_ref()
+                .map(|saved| saved.galaxy != masterok.settings())
+            {
+                //restore_attract_layers takes it from here once the galaxy exists again
+                Some(true) => {
+                    if let Some(saved) = &attract.restore {
+                        masterok.apply_settings(&saved.galaxy);
+                    }
+                    ev_change_seed.send(ChangeSeed);
+                    attract.regenerating = true;
+                }
+                //nothing was swapped, the selection and everything else are still there
+                Some(false) => attract.restore = None,
+                None => (),
+            }
+        }
+
+        if settings.enabled
+            && attract.idle >= settings.idle_seconds
+            && attract.restore.is_none()
+            && !masterok.block_input
+        {
+            let mut rng = SmallRng::seed_from_u64(
+                masterok.gen_seed ^ ATTRACT_SALT ^ time.elapsed().as_nanos() as u64,
+            );
+            start_drift(
+                &mut rng,
+                &masterok.positions,
+                &limits,
+                settings.galaxy_seconds,
+                &mut controller,
+            );
+            attract.run = Some(AttractRun {
+                phase: AttractPhase::Drifting,
+                elapsed: 0.0,
+                rng,
+                saved: AttractReturn {
+                    galaxy: masterok.settings(),
+                    camera: Some(SavedCamera {
+                        translation: transform.translation.truncate(),
+                        scale: projection.scale,
+                    }),
+                    layers: layers.capture(),
+                },
+                capture: default(),
+                captured: false,
+            });
+        }
+        return;
+    }
+
+    let Some(run) = attract.run.as_mut() else {
+        return;
+    };
+    run.elapsed += time.delta_seconds();
+
+    match run.phase {
+        AttractPhase::Drifting => {
+            if run.elapsed >= (settings.galaxy_seconds - FADE_SECONDS).max(FADE_SECONDS) {
+                //read back a frame or two later, an error only means someone else is taking one
+                if let Ok(window) = window_query.get_single() {
+                    let capture = run.capture.clone();
+                    let _ = screenshots.take_screenshot(window, move |mut image| {
+                        //with hdr on the alpha channel holds brightness instead of coverage
+                        for pixel in image.data.chunks_exact_mut(4) {
+                            pixel[3] = u8::MAX;
+                        }
+                        if let Ok(mut capture) = capture.lock() {
+                            *capture = Some(image);
+                        }
+                    });
+                }
+                run.phase = AttractPhase::Capturing;
+                run.elapsed = 0.0;
+                run.captured = false;
+            }
+        }
+        AttractPhase::Capturing => {
+            if let Some(image) = run
+                .capture
+                .lock()
+                .ok()
+                .and_then(|mut capture| capture.take())
+            {
+                match &attract.snapshot {
+                    Some(handle) => images.insert(handle, image),
+                    None => attract.snapshot = Some(images.add(image)),
+                }
+                run.captured = true;
+            }
+            if (run.captured || run.elapsed >= CAPTURE_TIMEOUT) && !masterok.block_input {
+                let galaxy = random_settings(&mut run.rng);
+                masterok.apply_settings(&galaxy);
+                ev_change_seed.send(ChangeSeed);
+                run.phase = AttractPhase::Generating;
+                run.elapsed = 0.0;
+            }
+        }
+        AttractPhase::Generating => {
+            if generated {
+                //the camera limits have already been updated for the new galaxy
+                start_drift(
+                    &mut run.rng,
+                    &masterok.positions,
+                    &limits,
+                    settings.galaxy_seconds,
+                    &mut controller,
+                );
+                run.phase = AttractPhase::FadingIn;
+                run.elapsed = 0.0;
+            }
+        }
+        AttractPhase::FadingIn => {
+            if run.elapsed >= FADE_SECONDS {
+                run.phase = AttractPhase::Drifting;
+            }
+        }
+    }
+}
+
+//once the player's galaxy has been regenerated, puts back the selection, route, capitals and the rest
+//that regenerating cleared, like loading a save does
+pub fn restore_attract_layers(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut attract: ResMut<AttractMode>,
+    mut layers: SessionLayers,
+    mut ev_selected: EventWriter<StarSelected>,
+    mut query_camera: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Some(generated) = ev_generated.read().last() else {
+        return;
+    };
+    //the end of a galaxy attract mode was still generating when the player came back
+    if !attract.regenerating
+        || attract
+            .restore
+            .as_ref()
+            .map_or(true, |saved| saved.galaxy.gen_seed != generated.seed)
+    {
+        return;
+    }
+    attract.regenerating = false;
+    let Some(saved) = attract.restore.take() else {
+        return;
+    };
+
+    if let (Some(camera), Ok((mut transform, mut projection, mut controller))) =
+        (&saved.camera, query_camera.get_single_mut())
+    {
+        restore_camera(camera, &mut transform, &mut projection, &mut controller);
+    }
+    layers.restore(saved.layers, generated.seed);
+    ev_selected.send(StarSelected(layers.selected.index));
+}
+
+//the last frame of the outgoing galaxy over everything, fading out over the incoming one, black when
+//no screenshot could be taken
+pub fn attract_fade_system(mut contexts: EguiContexts, attract: Res<AttractMode>) {
+    let fade = attract.fade();
+    if fade <= 0.0 {
+        return;
+    }
+    let snapshot = attract
+        .run
+        .as_ref()
+        .filter(|run| run.captured)
+        .and(attract.snapshot.as_ref())
+        .map(|handle| contexts.add_image(handle.clone_weak()));
+
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("attract_fade"),
+    ));
+    let alpha = (fade * 255.0) as u8;
+    match snapshot {
+        Some(texture) => painter.image(
+            texture,
+            ctx.screen_rect(),
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::from_white_alpha(alpha),
+        ),
+        None => painter.rect_filled(
+            ctx.screen_rect(),
+            0.0,
+            egui::Color32::from_black_alpha(alpha),
+        ),
+    };
+}
+
+pub fn attract_settings_ui(ui: &mut egui::Ui, settings: &mut AttractSettings) {
+    egui::CollapsingHeader::new("Attract Mode")
+        .id_source("attract_mode")
+        .show(ui, |ui| {
+            ui.checkbox(&mut settings.enabled, "Start When Idle");
+            ui.add(
+                egui::Slider::new(&mut settings.idle_seconds, 10.0..=1800.0)
+                    .logarithmic(true)
+                    .text("Idle (s)"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.galaxy_seconds, 10.0..=300.0)
+                    .text("Per Galaxy (s)"),
+            );
+            ui.small("Cycles through random galaxies until any input");
+        });
+}
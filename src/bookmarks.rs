@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{CameraController, CameraLimits, GalaxyCamera};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::input::{Action, ActionState, KeyMap};
+use crate::resources::{GalaxyGenerated, GalaxySettings, Masterik};
+use crate::storage;
+
+//digits 1-9 jump to the first nine bookmarks
+pub const BOOKMARK_KEYS: u8 = 9;
+
+//a named view of the galaxy
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub translation: Vec2,
+    pub scale: f32,
+}
+
+//bookmarks of the current galaxy, saved per galaxy whenever they change
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct Bookmarks {
+    //seed of the galaxy these belong to, None until a galaxy has been generated
+    pub galaxy: Option<u64>,
+    pub list: Vec<Bookmark>,
+}
+
+//what goes to disk, positions only mean something in the galaxy they were taken in
+#[derive(Serialize, Deserialize)]
+struct SavedBookmarks {
+    settings: GalaxySettings,
+    bookmarks: Vec<Bookmark>,
+}
+
+//bookmarks from a share code, merged in once their galaxy has been generated
+#[derive(Resource, Default)]
+pub struct IncomingBookmarks {
+    pub settings: Option<GalaxySettings>,
+    pub list: Vec<Bookmark>,
+}
+
+//name typed into the panel for the next bookmark
+#[derive(Resource, Default)]
+pub struct BookmarkDraft {
+    pub name: String,
+}
+
+#[derive(Event)]
+pub struct AddBookmark(pub String);
+
+#[derive(Event)]
+pub struct JumpToBookmark(pub usize);
+
+impl Bookmarks {
+    fn storage_key(seed: u64) -> String {
+        format!("bookmarks-{}", seed)
+    }
+
+    //same name replaces the old view, anything else is added at the end
+    pub fn merge(&mut self, incoming: Vec<Bookmark>) {
+        for bookmark in incoming {
+            match self.list.iter_mut().find(|old| old.name == bookmark.name) {
+                Some(old) => *old = bookmark,
+                None => self.list.push(bookmark),
+            }
+        }
+    }
+
+    pub fn load(settings: &GalaxySettings) -> Result<Vec<Bookmark>, GalaxyError> {
+        let Some(text) = storage::read_text(&Self::storage_key(settings.gen_seed))
+            .map_err(GalaxyError::Storage)?
+        else {
+            return Ok(Vec::new());
+        };
+        let saved: SavedBookmarks =
+            ron::from_str(&text).map_err(|err| GalaxyError::Save(format!("bookmarks: {}", err)))?;
+
+        //a different galaxy under the same seed
+        if saved.settings != *settings {
+            return Ok(Vec::new());
+        }
+        Ok(saved.bookmarks)
+    }
+
+    pub fn save(&self, settings: GalaxySettings) -> Result<(), GalaxyError> {
+        let saved = SavedBookmarks {
+            settings,
+            bookmarks: self.list.clone(),
+        };
+        let text = ron::ser::to_string_pretty(&saved, Default::default())
+            .map_err(|err| GalaxyError::Save(format!("bookmarks: {}", err)))?;
+        storage::write_text(&Self::storage_key(saved.settings.gen_seed), &text)
+            .map_err(GalaxyError::Storage)
+    }
+}
+
+pub fn load_bookmarks_on_regenerate(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    masterok: Res<Masterik>,
+    mut bookmarks: ResMut<Bookmarks>,
+    mut incoming: ResMut<IncomingBookmarks>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if ev_generated.read().count() == 0 {
+        return;
+    }
+
+    let settings = masterok.settings();
+    let list = Bookmarks::load(&settings).unwrap_or_else(|err| {
+        toasts.report(&err);
+        Vec::new()
+    });
+    //loading is not a change, otherwise every generated galaxy would leave a file behind
+    *bookmarks.bypass_change_detection() = Bookmarks {
+        galaxy: Some(masterok.gen_seed),
+        list,
+    };
+
+    //a share code for some other galaxy that never got generated is dropped
+    if let Some(shared) = incoming.settings.take() {
+        let shared_list = std::mem::take(&mut incoming.list);
+        if shared == settings && !shared_list.is_empty() {
+            bookmarks.merge(shared_list);
+        }
+    }
+}
+
+pub fn save_bookmarks(
+    masterok: Res<Masterik>,
+    bookmarks: Res<Bookmarks>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if !bookmarks.is_changed()
+        || masterok.block_input
+        || bookmarks.galaxy != Some(masterok.gen_seed)
+    {
+        return;
+    }
+    if let Err(err) = bookmarks.save(masterok.settings()) {
+        toasts.report(&err);
+    }
+}
+
+pub fn bookmark_keys_system(actions: Res<ActionState>, mut ev_jump: EventWriter<JumpToBookmark>) {
+    for key in 0..BOOKMARK_KEYS {
+        if actions.just_pressed(Action::Bookmark(key)) {
+            ev_jump.send(JumpToBookmark(key as usize));
+        }
+    }
+}
+
+//unnamed bookmarks are numbered
+pub fn add_bookmark_system(
+    mut ev_add: EventReader<AddBookmark>,
+    masterok: Res<Masterik>,
+    mut bookmarks: ResMut<Bookmarks>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<GalaxyCamera>>,
+) {
+    let Some(AddBookmark(name)) = ev_add.read().last() else {
+        return;
+    };
+    let Ok((transform, projection)) = query_camera.get_single() else {
+        return;
+    };
+    if masterok.block_input {
+        return;
+    }
+
+    let name = match name.trim() {
+        "" => format!("View {}", bookmarks.list.len() + 1),
+        name => name.to_string(),
+    };
+    bookmarks.merge(vec![Bookmark {
+        name,
+        translation: transform.translation.truncate(),
+        scale: projection.scale,
+    }]);
+}
+
+pub fn jump_to_bookmark_system(
+    mut ev_jump: EventReader<JumpToBookmark>,
+    bookmarks: Res<Bookmarks>,
+    limits: Res<CameraLimits>,
+    mut query_camera: Query<
+        (&OrthographicProjection, &Transform, &mut CameraController),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Some(bookmark) = ev_jump
+        .read()
+        .last()
+        .and_then(|JumpToBookmark(index)| bookmarks.list.get(*index))
+    else {
+        return;
+    };
+    let Ok((projection, transform, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+    controller.fly_to(
+        transform.translation.truncate(),
+        projection.scale,
+        bookmark.translation,
+        bookmark.scale.clamp(limits.min_scale, limits.max_scale),
+    );
+}
+
+pub enum BookmarkAction {
+    Add,
+    Jump(usize),
+}
+
+pub fn bookmarks_ui(
+    ui: &mut egui::Ui,
+    bookmarks: &mut Bookmarks,
+    draft: &mut BookmarkDraft,
+    keymap: &KeyMap,
+) -> Option<BookmarkAction> {
+    let mut action = None;
+    egui::CollapsingHeader::new("Bookmarks")
+        .id_source("bookmarks")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.name)
+                        .hint_text("Name")
+                        .desired_width(140.0),
+                );
+                if ui.button("Add Current View").clicked() {
+                    action = Some(BookmarkAction::Add);
+                }
+            });
+
+            let mut removed = None;
+            for (index, bookmark) in bookmarks.list.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let key = if index < BOOKMARK_KEYS as usize {
+                        keymap.label(Action::Bookmark(index as u8))
+                    } else {
+                        "-".into()
+                    };
+                    ui.label(format!("[{}]", key));
+                    if ui.button(&bookmark.name).clicked() {
+                        action = Some(BookmarkAction::Jump(index));
+                    }
+                    if ui.small_button("x").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                bookmarks.list.remove(index);
+            }
+
+            if bookmarks.list.is_empty() {
+                ui.small("Saved per galaxy, number keys jump to them");
+            }
+        });
+    action
+}
@@ -0,0 +1,355 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::resources::{Masterik, UiFocus};
+
+//marks the camera the galaxy controls steer, embedding apps can put it on their own camera
+#[derive(Component)]
+pub struct GalaxyCamera;
+
+//zoom state of the galaxy camera, input only ever touches target_scale and the projection eases towards it
+#[derive(Component)]
+pub struct CameraController {
+    pub target_scale: f32,
+    //screen offset from the viewport center (y up) that should stay fixed while zooming
+    pub zoom_focus: Vec2,
+    pub flight: Option<CameraFlight>,
+}
+
+impl CameraController {
+    pub fn new(scale: f32) -> Self {
+        Self {
+            target_scale: scale,
+            zoom_focus: Vec2::ZERO,
+            flight: None,
+        }
+    }
+
+    //multiplies the target zoom, factor > 1 zooms out, zooming by hand ends a flight
+    pub fn zoom_by(&mut self, factor: f32, focus: Vec2, limits: &CameraLimits) {
+        self.target_scale = (self.target_scale * factor).clamp(limits.min_scale, limits.max_scale);
+        self.zoom_focus = focus;
+        self.flight = None;
+    }
+
+    //glides from the current view to a point and zoom level
+    pub fn fly_to(&mut self, from: Vec2, from_scale: f32, to: Vec2, to_scale: f32) {
+        let distance_in_screens = from.distance(to) / from_scale.max(to_scale).max(1.0);
+        self.flight = Some(CameraFlight {
+            from,
+            to,
+            from_scale,
+            to_scale,
+            elapsed: 0.0,
+            //long trips take a bit longer but never drag on
+            duration: (0.6 + distance_in_screens.sqrt() * 0.05).min(2.5),
+        });
+        self.target_scale = to_scale;
+        self.zoom_focus = Vec2::ZERO;
+    }
+}
+
+//a camera animation between two views, position and zoom share one eased timeline
+pub struct CameraFlight {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub from_scale: f32,
+    pub to_scale: f32,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl CameraFlight {
+    //position and scale at the current point of the flight
+    fn sample(&self) -> (Vec2, f32) {
+        let t = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        //smootherstep, starts and stops gently
+        let eased = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        //zoom is interpolated in log space so it feels even at every level
+        let scale =
+            (self.from_scale.ln() + (self.to_scale.ln() - self.from_scale.ln()) * eased).exp();
+        (self.from.lerp(self.to, eased), scale)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[derive(Resource)]
+pub struct CameraLimits {
+    pub min_scale: f32,
+    pub max_scale: f32,
+    //camera can't leave this circle around the galactic core
+    pub pan_radius: f32,
+    //higher is snappier, 0 would never arrive
+    pub zoom_smoothing: f32,
+}
+
+impl Default for CameraLimits {
+    fn default() -> Self {
+        Self {
+            min_scale: 2.0,
+            max_scale: 20000.0,
+            pan_radius: 5000000.0,
+            zoom_smoothing: 12.0,
+        }
+    }
+}
+
+//mouse drag bookkeeping, moved is how far the cursor travelled since the button went down
+#[derive(Resource, Default)]
+pub struct PointerDrag {
+    pub active: bool,
+    pub last_position: Option<Vec2>,
+    pub moved: f32,
+}
+
+//touch gesture from the previous frame
+#[derive(Default)]
+pub struct TouchGesture {
+    pinch_distance: Option<f32>,
+    midpoint: Option<Vec2>,
+}
+
+//cursor position relative to the viewport center with y pointing up, same orientation as the world
+fn screen_offset(window: &Window, cursor: Vec2) -> Vec2 {
+    let center = Vec2::new(window.width(), window.height()) / 2.0;
+    Vec2::new(cursor.x - center.x, center.y - cursor.y)
+}
+
+fn pan_by_screen_delta(transform: &mut Transform, delta: Vec2, scale: f32) {
+    //screen y grows downwards
+    let world_delta = transform.rotation * Vec3::new(-delta.x, delta.y, 0.0) * scale;
+    transform.translation += world_delta;
+}
+
+pub fn mouse_camera_system(
+    focus: Res<UiFocus>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut ev_wheel: EventReader<MouseWheel>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    limits: Res<CameraLimits>,
+    masterok: Res<Masterik>,
+    mut drag: ResMut<PointerDrag>,
+    mut query_camera: Query<
+        (
+            &OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((projection, mut transform, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+
+    let egui_busy = focus.pointer_over_ui;
+    let cursor = window.cursor_position();
+
+    //scroll wheel zoom around the cursor
+    let mut scroll = 0.0;
+    for ev in ev_wheel.read() {
+        scroll += match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        };
+    }
+    if scroll != 0.0 && !egui_busy && !masterok.block_input {
+        let focus = cursor.map_or(Vec2::ZERO, |c| screen_offset(window, c));
+        controller.zoom_by(0.85_f32.powf(scroll), focus, &limits);
+    }
+
+    //left button drag pans the camera
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        drag.active = !egui_busy;
+        drag.last_position = cursor;
+        drag.moved = 0.0;
+    }
+    if mouse_buttons.just_released(MouseButton::Left) {
+        drag.active = false;
+    }
+
+    if drag.active && mouse_buttons.pressed(MouseButton::Left) {
+        if let (Some(last), Some(current)) = (drag.last_position, cursor) {
+            let delta = current - last;
+            drag.moved += delta.length();
+            if delta != Vec2::ZERO {
+                controller.flight = None;
+            }
+            pan_by_screen_delta(&mut transform, delta, projection.scale);
+        }
+        drag.last_position = cursor;
+    }
+}
+
+//one finger pans, two fingers pan with their midpoint and pinch to zoom
+pub fn touch_camera_system(
+    focus: Res<UiFocus>,
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    limits: Res<CameraLimits>,
+    mut gesture: Local<TouchGesture>,
+    mut query_camera: Query<
+        (
+            &mut OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((mut projection, mut transform, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+
+    //fingers on the panel scroll and drag egui, the gesture starts over once they leave it
+    if focus.pointer_over_ui {
+        *gesture = TouchGesture::default();
+        return;
+    }
+
+    let active: Vec<&bevy::input::touch::Touch> = touches.iter().collect();
+
+    match active.as_slice() {
+        [touch] => {
+            pan_by_screen_delta(&mut transform, touch.delta(), projection.scale);
+            *gesture = TouchGesture::default();
+        }
+        [first, second, ..] => {
+            let midpoint = (first.position() + second.position()) / 2.0;
+            let distance = first.position().distance(second.position());
+
+            if let Some(previous_midpoint) = gesture.midpoint {
+                pan_by_screen_delta(
+                    &mut transform,
+                    midpoint - previous_midpoint,
+                    projection.scale,
+                );
+            }
+
+            if let Some(previous_distance) = gesture.pinch_distance {
+                if distance > 1.0 {
+                    //pinching is already smooth, so the projection follows the fingers directly
+                    let factor = previous_distance / distance;
+                    let focus = screen_offset(window, midpoint);
+                    controller.zoom_by(factor, focus, &limits);
+                    let old_scale = projection.scale;
+                    projection.scale = controller.target_scale;
+                    let shift =
+                        transform.rotation * (focus * (old_scale - projection.scale)).extend(0.0);
+                    transform.translation += shift;
+                }
+            }
+
+            gesture.midpoint = Some(midpoint);
+            gesture.pinch_distance = Some(distance);
+        }
+        [] => *gesture = TouchGesture::default(),
+    }
+}
+
+//cameras brought by the embedding app get a controller when they are tagged
+pub fn attach_camera_controller(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &OrthographicProjection),
+        (With<GalaxyCamera>, Without<CameraController>),
+    >,
+) {
+    for (entity, projection) in query.iter() {
+        commands
+            .entity(entity)
+            .insert(CameraController::new(projection.scale));
+    }
+}
+
+//eases the projection towards the target zoom while keeping the focus point under the cursor
+pub fn smooth_camera_system(
+    time: Res<Time>,
+    limits: Res<CameraLimits>,
+    mut query_camera: Query<
+        (
+            &mut OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    for (mut projection, mut transform, mut controller) in query_camera.iter_mut() {
+        if let Some(flight) = controller.flight.as_mut() {
+            flight.elapsed += time.delta_seconds();
+            let (position, scale) = flight.sample();
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+            projection.scale = scale;
+            if flight.finished() {
+                controller.flight = None;
+            }
+            continue;
+        }
+
+        let difference = controller.target_scale - projection.scale;
+        if difference.abs() > f32::EPSILON {
+            let blend = 1.0 - (-limits.zoom_smoothing * time.delta_seconds()).exp();
+            let old_scale = projection.scale;
+            projection.scale = if difference.abs() < old_scale * 0.001 {
+                controller.target_scale
+            } else {
+                old_scale + difference * blend
+            };
+
+            let shift = transform.rotation
+                * (controller.zoom_focus * (old_scale - projection.scale)).extend(0.0);
+            transform.translation += shift;
+        }
+
+        let flat = transform.translation.truncate();
+        if flat.length() > limits.pan_radius {
+            let clamped = flat.normalize() * limits.pan_radius;
+            transform.translation.x = clamped.x;
+            transform.translation.y = clamped.y;
+        }
+    }
+}
+
+//recomputes how far out the camera may go whenever the amount of stars changes
+pub fn update_camera_limits(
+    masterok: Res<Masterik>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut limits: ResMut<CameraLimits>,
+    mut last_len: Local<usize>,
+) {
+    if masterok.block_input || masterok.positions.len() == *last_len {
+        return;
+    }
+    *last_len = masterok.positions.len();
+
+    let extent = masterok
+        .positions
+        .iter()
+        .map(|&(x, y, _)| Vec2::new(x, y).length())
+        .fold(0.0_f32, f32::max);
+    if extent <= 0.0 {
+        return;
+    }
+
+    let smallest_side = window_query
+        .get_single()
+        .map(|w| w.width().min(w.height()))
+        .unwrap_or(720.0)
+        .max(1.0);
+
+    limits.pan_radius = extent * 1.1;
+    //zoomed out as far as possible the whole galaxy still fills a good part of the screen
+    limits.max_scale = (extent * 3.0 / smallest_side).max(limits.min_scale * 2.0);
+}
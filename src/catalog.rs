@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::Serialize;
+
+use crate::economy::{StarEconomy, StarResources};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::factions::{Faction, Territories};
+use crate::lanes::StarLanes;
+use crate::planets::{StarSystem, SystemCache};
+use crate::resources::{GalaxySettings, Masterik, StarClass, StarData};
+use crate::selection::StarInfo;
+use crate::storage::write_export;
+
+//asks for the current galaxy to be written out as json
+#[derive(Event)]
+pub struct ExportCatalog;
+
+//where the last export ended up, shown under the export button
+#[derive(Resource, Default)]
+pub struct CatalogExport {
+    pub last_path: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CatalogStar {
+    pub index: usize,
+    pub name: String,
+    pub designation: String,
+    pub class: StarClass,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    //index into the factions list
+    pub faction: Option<usize>,
+    pub resources: Option<StarResources>,
+}
+
+#[derive(Serialize)]
+pub struct Catalog<'a> {
+    pub settings: GalaxySettings,
+    pub stars: Vec<CatalogStar>,
+    //only the systems generated so far, the rest can be rebuilt from the seed and star index
+    pub systems: Vec<&'a StarSystem>,
+    //pairs of star indices
+    pub lanes: &'a [(usize, usize)],
+    pub factions: &'a [Faction],
+}
+
+impl<'a> Catalog<'a> {
+    pub fn new(
+        masterok: &Masterik,
+        star_data: &StarData,
+        cache: Option<&'a SystemCache>,
+        lanes: Option<&'a StarLanes>,
+        territories: Option<&'a Territories>,
+        economy: Option<&StarEconomy>,
+    ) -> Self {
+        let stars = (0..masterok.positions.len())
+            .filter_map(|index| StarInfo::new(index, masterok, star_data))
+            .map(|info| CatalogStar {
+                index: info.index,
+                name: info.name,
+                designation: info.designation,
+                class: info.class,
+                x: info.position.x,
+                y: info.position.y,
+                radius: info.radius,
+                faction: territories
+                    .and_then(|territories| territories.owners.get(info.index).copied().flatten()),
+                resources: economy.and_then(|economy| economy.get(info.index).copied()),
+            })
+            .collect();
+
+        Self {
+            settings: masterok.settings(),
+            stars,
+            systems: cache.map_or_else(Vec::new, SystemCache::sorted),
+            lanes: lanes.map_or(&[], |lanes| &lanes.lanes),
+            factions: territories.map_or(&[], |territories| &territories.factions),
+        }
+    }
+
+    pub fn export(&self) -> Result<String, GalaxyError> {
+        let text =
+            serde_json::to_string_pretty(self).map_err(|err| GalaxyError::Storage(err.into()))?;
+        let file_name = format!("catalog-{}.json", self.settings.gen_seed);
+        write_export(&file_name, &text)
+            .map(|path| path.display().to_string())
+            .map_err(GalaxyError::Storage)
+    }
+}
+
+pub fn export_catalog_system(
+    mut ev_export: EventReader<ExportCatalog>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    cache: Option<Res<SystemCache>>,
+    lanes: Option<Res<StarLanes>>,
+    territories: Option<Res<Territories>>,
+    economy: Option<Res<StarEconomy>>,
+    mut export: ResMut<CatalogExport>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if ev_export.read().count() == 0 || masterok.block_input {
+        return;
+    }
+
+    match Catalog::new(
+        &masterok,
+        &star_data,
+        cache.as_deref(),
+        lanes.as_deref(),
+        territories.as_deref(),
+        economy.as_deref(),
+    )
+    .export()
+    {
+        Ok(path) => {
+            info!("catalog written to {}", path);
+            export.last_path = Some(path);
+        }
+        Err(err) => toasts.report(&err),
+    }
+}
+
+pub fn catalog_export_ui(ui: &mut egui::Ui, export: &CatalogExport) -> bool {
+    let clicked = ui.button("Export Catalog").clicked();
+    if let Some(path) = &export.last_path {
+        ui.small(format!("Saved to {}", path));
+    }
+    clicked
+}
@@ -0,0 +1,463 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::camera::{CameraController, GalaxyCamera};
+use crate::input::{Action, ActionState, KeyMap};
+
+//manually added keyframes land this long after the last one
+const KEYFRAME_GAP: f32 = 2.0;
+//keyframes can't be dragged closer together than this
+const MIN_KEYFRAME_SPACING: f32 = 0.05;
+
+//one point of a camera path, rotation is around the screen axis in radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    pub translation: Vec2,
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl Keyframe {
+    //zoom is splined in log space so it feels even at every level, like camera flights
+    fn values(&self, rotation: f32) -> Vec4 {
+        Vec4::new(
+            self.translation.x,
+            self.translation.y,
+            self.scale.max(f32::EPSILON).ln(),
+            rotation,
+        )
+    }
+}
+
+//the same angle in -pi..pi
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+//keyframes sorted by time, the first one is where playback starts
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    //catmull-rom through the keyframes, tangents respect uneven spacing so edited timings stay smooth
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let keys = &self.keyframes;
+        let first = keys.first()?;
+        let last = keys.last()?;
+        if keys.len() == 1 || time <= first.time {
+            return Some(*first);
+        }
+        if time >= last.time {
+            return Some(*last);
+        }
+
+        let segment = keys.windows(2).position(|pair| time < pair[1].time)?;
+        let (a, b) = (&keys[segment], &keys[segment + 1]);
+
+        //every keyframe turns the short way from the one before, 179° to -179° is 2° and not 358°
+        let mut rotations = Vec::with_capacity(keys.len());
+        for key in keys {
+            let rotation = match rotations.last() {
+                Some(&previous) => previous + wrap_angle(key.rotation - previous),
+                None => key.rotation,
+            };
+            rotations.push(rotation);
+        }
+        let values = |index: usize| keys[index].values(rotations[index]);
+        let span = (b.time - a.time).max(f32::EPSILON);
+        let t = (time - a.time) / span;
+
+        //the ends have no outer neighbour and fall back to a one sided difference
+        let tangent = |index: usize| {
+            let (previous, next) = (index.saturating_sub(1), (index + 1).min(keys.len() - 1));
+            (values(next) - values(previous))
+                / (keys[next].time - keys[previous].time).max(f32::EPSILON)
+        };
+        let (m0, m1) = (tangent(segment) * span, tangent(segment + 1) * span);
+
+        let (t2, t3) = (t * t, t * t * t);
+        let sampled = values(segment) * (2.0 * t3 - 3.0 * t2 + 1.0)
+            + m0 * (t3 - 2.0 * t2 + t)
+            + values(segment + 1) * (-2.0 * t3 + 3.0 * t2)
+            + m1 * (t3 - t2);
+
+        Some(Keyframe {
+            time,
+            translation: sampled.truncate().truncate(),
+            scale: sampled.z.exp(),
+            rotation: wrap_angle(sampled.w),
+        })
+    }
+
+    //stretches every keyframe so the path takes this long
+    pub fn set_duration(&mut self, duration: f32) {
+        let old = self.duration();
+        if old <= 0.0 {
+            return;
+        }
+        for keyframe in self.keyframes.iter_mut() {
+            keyframe.time *= duration / old;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PathEasing {
+    Linear,
+    //starts and stops gently, the middle goes faster to make up for it
+    #[default]
+    EaseInOut,
+}
+
+impl PathEasing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            PathEasing::Linear => t,
+            PathEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+//recording and playback state, the panel is hidden while playback is running
+#[derive(Resource)]
+pub struct Cinematic {
+    pub recording: bool,
+    //seconds between keyframes taken while recording
+    pub record_interval: f32,
+    pub easing: PathEasing,
+    //seconds into the path, None when not playing
+    pub playback: Option<f32>,
+    record_clock: f32,
+}
+
+impl Default for Cinematic {
+    fn default() -> Self {
+        Self {
+            recording: false,
+            record_interval: 1.0,
+            easing: PathEasing::default(),
+            playback: None,
+            record_clock: 0.0,
+        }
+    }
+}
+
+impl Cinematic {
+    //recording always starts a new path
+    pub fn start_recording(&mut self, path: &mut CameraPath) {
+        path.keyframes.clear();
+        self.recording = true;
+        self.playback = None;
+        self.record_clock = 0.0;
+    }
+
+    pub fn start_playback(&mut self, path: &CameraPath) {
+        if path.keyframes.len() >= 2 {
+            self.recording = false;
+            self.playback = Some(0.0);
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct AddKeyframe;
+
+pub fn camera_path_keys_system(
+    actions: Res<ActionState>,
+    mut cinematic: ResMut<Cinematic>,
+    mut path: ResMut<CameraPath>,
+) {
+    if actions.just_pressed(Action::RecordCameraPath) {
+        if cinematic.recording {
+            cinematic.recording = false;
+        } else {
+            cinematic.start_recording(&mut path);
+        }
+    }
+    if actions.just_pressed(Action::PlayCameraPath) {
+        if cinematic.playback.is_some() {
+            cinematic.playback = None;
+        } else {
+            cinematic.start_playback(&path);
+        }
+    }
+}
+
+//takes a keyframe every record_interval while recording, and one per AddKeyframe
+pub fn record_camera_path(
+    time: Res<Time>,
+    mut ev_keyframe: EventReader<AddKeyframe>,
+    mut cinematic: ResMut<Cinematic>,
+    mut path: ResMut<CameraPath>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<GalaxyCamera>>,
+) {
+    let added = ev_keyframe.read().count() > 0;
+    let Ok((transform, projection)) = query_camera.get_single() else {
+        return;
+    };
+
+    let keyframe_time = if cinematic.recording {
+        cinematic.record_clock += time.delta_seconds();
+        let due = path
+            .keyframes
+            .last()
+            .map_or(0.0, |last| last.time + cinematic.record_interval);
+        (cinematic.record_clock >= due).then_some(cinematic.record_clock.max(due))
+    } else {
+        added.then(|| {
+            path.keyframes
+                .last()
+                .map_or(0.0, |last| last.time + KEYFRAME_GAP)
+        })
+    };
+
+    if let Some(keyframe_time) = keyframe_time {
+        //the first keyframe always starts the path
+        let time = if path.keyframes.is_empty() {
+            0.0
+        } else {
+            keyframe_time
+        };
+        path.keyframes.push(Keyframe {
+            time,
+            translation: transform.translation.truncate(),
+            scale: projection.scale,
+            rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+        });
+    }
+}
+
+//drives the camera directly, anything the input systems did this frame is overwritten, nothing
+//else rolls the camera so it is put back upright however playback ends
+pub fn play_camera_path(
+    time: Res<Time>,
+    path: Res<CameraPath>,
+    mut cinematic: ResMut<Cinematic>,
+    mut query_camera: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+    mut playing: Local<bool>,
+) {
+    let Ok((mut transform, mut projection, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+    let Some(elapsed) = cinematic.playback else {
+        if std::mem::take(&mut *playing) {
+            transform.rotation = Quat::IDENTITY;
+        }
+        return;
+    };
+    *playing = true;
+    let duration = path.duration();
+
+    let elapsed = elapsed + time.delta_seconds();
+    let progress = if duration > 0.0 {
+        (elapsed / duration).min(1.0)
+    } else {
+        1.0
+    };
+    if let Some(view) = path.sample(cinematic.easing.apply(progress) * duration) {
+        transform.translation = view.translation.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(view.rotation);
+        projection.scale = view.scale;
+        controller.target_scale = view.scale;
+        controller.flight = None;
+    }
+
+    cinematic.playback = (progress < 1.0).then_some(elapsed);
+}
+
+pub fn camera_path_ui(
+    ui: &mut egui::Ui,
+    path: &mut CameraPath,
+    cinematic: &mut Cinematic,
+    keymap: &KeyMap,
+) -> bool {
+    let mut add_keyframe = false;
+    egui::CollapsingHeader::new("Camera Path")
+        .id_source("camera_path")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let record = format!(
+                    "{} [{}]",
+                    if cinematic.recording {
+                        "Stop"
+                    } else {
+                        "Record"
+                    },
+                    keymap.label(Action::RecordCameraPath)
+                );
+                if ui.button(record).clicked() {
+                    if cinematic.recording {
+                        cinematic.recording = false;
+                    } else {
+                        cinematic.start_recording(path);
+                    }
+                }
+                if ui
+                    .add_enabled(
+                        path.keyframes.len() >= 2,
+                        egui::Button::new(format!(
+                            "Play [{}]",
+                            keymap.label(Action::PlayCameraPath)
+                        )),
+                    )
+                    .clicked()
+                {
+                    cinematic.start_playback(path);
+                }
+                if ui.button("Add Keyframe").clicked() {
+                    add_keyframe = true;
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut cinematic.record_interval, 0.25..=5.0)
+                    .text("Record every (s)"),
+            );
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut cinematic.easing, PathEasing::EaseInOut, "Ease In/Out");
+                ui.radio_value(&mut cinematic.easing, PathEasing::Linear, "Linear");
+            });
+
+            if path.keyframes.len() < 2 {
+                ui.small("Record while flying around, or add keyframes by hand");
+                return;
+            }
+
+            let mut duration = path.duration();
+            if ui
+                .add(
+                    egui::DragValue::new(&mut duration)
+                        .speed(0.1)
+                        .clamp_range(1.0..=600.0)
+                        .prefix("Duration: ")
+                        .suffix(" s"),
+                )
+                .changed()
+            {
+                path.set_duration(duration);
+            }
+
+            //each keyframe stays between its neighbours so the path never runs backwards
+            let mut removed = None;
+            let times: Vec<f32> = path
+                .keyframes
+                .iter()
+                .map(|keyframe| keyframe.time)
+                .collect();
+            egui::Grid::new("camera_path_keyframes")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (index, keyframe) in path.keyframes.iter_mut().enumerate() {
+                        ui.label(format!("{}", index + 1));
+                        let min = match index {
+                            0 => 0.0,
+                            _ => times[index - 1] + MIN_KEYFRAME_SPACING,
+                        };
+                        let max = times
+                            .get(index + 1)
+                            .map_or(f32::MAX, |next| next - MIN_KEYFRAME_SPACING);
+                        ui.add(
+                            egui::DragValue::new(&mut keyframe.time)
+                                .speed(0.05)
+                                .clamp_range(min..=max.max(min))
+                                .suffix(" s"),
+                        );
+                        ui.add(
+                            egui::DragValue::from_get_set(|value| {
+                                if let Some(degrees) = value {
+                                    keyframe.rotation = (degrees as f32).to_radians();
+                                }
+                                keyframe.rotation.to_degrees() as f64
+                            })
+                            .speed(1.0)
+                            .suffix("°"),
+                        );
+                        if ui.small_button("x").clicked() {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = removed {
+                path.keyframes.remove(index);
+                //the path always starts at 0
+                if let Some(first) = path.keyframes.first().map(|keyframe| keyframe.time) {
+                    for keyframe in path.keyframes.iter_mut() {
+                        keyframe.time -= first;
+                    }
+                }
+            }
+        });
+    add_keyframe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, x: f32, scale: f32, degrees: f32) -> Keyframe {
+        Keyframe {
+            time,
+            translation: Vec2::new(x, -x),
+            scale,
+            rotation: degrees.to_radians(),
+        }
+    }
+
+    fn path() -> CameraPath {
+        CameraPath {
+            keyframes: vec![
+                keyframe(0.0, 0.0, 1.0, 0.0),
+                keyframe(2.0, 1000.0, 4.0, 30.0),
+                keyframe(5.0, 3000.0, 2.0, 10.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn sample_holds_the_ends() {
+        let path = path();
+        assert_eq!(path.sample(-1.0), Some(path.keyframes[0]));
+        assert_eq!(path.sample(0.0), Some(path.keyframes[0]));
+        assert_eq!(path.sample(5.0), Some(path.keyframes[2]));
+        assert_eq!(path.sample(60.0), Some(path.keyframes[2]));
+        assert_eq!(CameraPath::default().sample(1.0), None);
+    }
+
+    #[test]
+    fn sample_passes_through_keyframes() {
+        let path = path();
+        let middle = path.sample(2.0).unwrap();
+        assert!(middle.translation.distance(path.keyframes[1].translation) < 0.01);
+        assert!((middle.scale - path.keyframes[1].scale).abs() < 0.001);
+        assert!((middle.rotation - path.keyframes[1].rotation).abs() < 0.001);
+    }
+
+    #[test]
+    fn rotation_turns_the_short_way() {
+        let path = CameraPath {
+            keyframes: vec![
+                keyframe(0.0, 0.0, 1.0, 170.0),
+                keyframe(1.0, 0.0, 1.0, -170.0),
+            ],
+        };
+        //halfway between 170° and -170° is 180°, not 0°
+        let halfway = path.sample(0.5).unwrap().rotation.to_degrees();
+        assert!(halfway.abs() > 175.0, "{}", halfway);
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use crate::camera::{CameraController, CameraLimits, GalaxyCamera};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::input::{Action, ActionState};
+use crate::resources::{ChangeSeed, Masterik, SpawnStars, StarClass};
+
+pub fn keyboard_input_system(
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    mut masterok: ResMut<Masterik>,
+    mut query_camera: Query<
+        (
+            &OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+    limits: Res<CameraLimits>,
+    mut toasts: ResMut<ErrorToasts>,
+    mut ev_spawn_stars: EventWriter<SpawnStars>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+) {
+    //Block input during star spawning process
+    if !masterok.block_input {
+        //zero or several cameras leave the keyboard without anything to steer
+        let (projection, mut transform, mut controller) = match query_camera.get_single_mut() {
+            Ok(camera) => camera,
+            Err(err) => {
+                toasts.report(&GalaxyError::Camera(err));
+                return;
+            }
+        };
+
+        let char_up = actions.pressed(Action::MoveUp);
+        let char_down = actions.pressed(Action::MoveDown);
+        let char_left = actions.pressed(Action::MoveLeft);
+        let char_right = actions.pressed(Action::MoveRight);
+
+        let char_q = actions.just_pressed(Action::ZoomOut);
+        let char_e = actions.just_pressed(Action::ZoomIn);
+
+        let add_angle = actions.just_pressed(Action::AddAngle);
+        let remove_angle = actions.just_pressed(Action::RemoveAngle);
+        let add_radius = actions.just_pressed(Action::AddRadius);
+        let remove_radius = actions.just_pressed(Action::RemoveRadius);
+        let add_distance = actions.just_pressed(Action::AddDistance);
+        let remove_distance = actions.just_pressed(Action::RemoveDistance);
+
+        let add_1000 = actions.just_pressed(Action::AddStars1000);
+        let remove_1000 = actions.just_pressed(Action::RemoveStars1000);
+        let add_10000 = actions.just_pressed(Action::AddStars10000);
+        let remove_10000 = actions.just_pressed(Action::RemoveStars10000);
+
+        let mut change_seed = actions.just_pressed(Action::ChangeSeed);
+        let reset_to_default = actions.just_pressed(Action::ResetToDefault);
+
+        let add_arm = actions.just_pressed(Action::AddArm);
+        let delete_arm = actions.just_pressed(Action::RemoveArm);
+
+        //change_seed gets set to true to trigger regeneration of stars to apply new settings, because star position generation is dependent on settings
+
+        if reset_to_default {
+            *masterok = Masterik::default();
+            change_seed = true;
+        }
+
+        if add_angle {
+            masterok.angle_mod += 0.0001;
+            change_seed = true;
+        }
+        if remove_angle && (masterok.angle_mod > 0.0001) {
+            masterok.angle_mod -= 0.0001;
+            change_seed = true;
+        }
+        if add_radius {
+            masterok.radius_mod += 300.0;
+            change_seed = true;
+        }
+        if remove_radius && (masterok.radius_mod > 310.0) {
+            masterok.radius_mod -= 200.0;
+            change_seed = true;
+        }
+
+        if add_distance {
+            masterok.distance_mod += 10.0;
+            change_seed = true;
+        }
+        if remove_distance && (masterok.distance_mod > 11.0) {
+            masterok.distance_mod -= 10.0;
+            change_seed = true;
+        }
+
+        //max 4 arms using simple algorithim
+        if add_arm && (masterok.spiral_arm_count < 4) {
+            masterok.spiral_arm_count += 1;
+            change_seed = true;
+        } else if delete_arm && (masterok.spiral_arm_count > 1) {
+            masterok.spiral_arm_count -= 1;
+            change_seed = true;
+        }
+
+        if add_1000 {
+            ev_spawn_stars.send(SpawnStars(1000));
+        } else if remove_1000 {
+            ev_spawn_stars.send(SpawnStars(-1000));
+        } else if add_10000 {
+            ev_spawn_stars.send(SpawnStars(10000));
+        } else if remove_10000 {
+            ev_spawn_stars.send(SpawnStars(-10000));
+        }
+
+        for class in StarClass::ALL {
+            if actions.just_pressed(Action::ToggleClass(class)) {
+                class.toggle(&mut masterok);
+                change_seed = true;
+            }
+        }
+
+        //steering by hand takes over from a running fly-to
+        if char_up || char_down || char_left || char_right || actions.pan != Vec2::ZERO {
+            controller.flight = None;
+        }
+
+        if char_up {
+            transform.translation.y += masterok.camera_move_speed * projection.scale;
+        }
+        if char_down {
+            transform.translation.y -= masterok.camera_move_speed * projection.scale;
+        }
+        if char_left {
+            transform.translation.x -= masterok.camera_move_speed * projection.scale;
+        }
+        if char_right {
+            transform.translation.x += masterok.camera_move_speed * projection.scale;
+        }
+
+        //analog stick pans proportional to how far it is pushed, 60 matches the per frame key speed at 60fps
+        if actions.pan != Vec2::ZERO {
+            let step = actions.pan
+                * masterok.camera_move_speed
+                * projection.scale
+                * 60.0
+                * time.delta_seconds();
+            let world_step = transform.rotation * step.extend(0.0);
+            transform.translation += world_step;
+        }
+        //full trigger doubles or halves the zoom every half second
+        if actions.zoom != 0.0 {
+            let factor = 2.0_f32.powf(-actions.zoom * 2.0 * time.delta_seconds());
+            controller.zoom_by(factor, Vec2::ZERO, &limits);
+        }
+
+        if char_q {
+            // zoom out
+            controller.zoom_by(2.0, Vec2::ZERO, &limits);
+        }
+        if char_e {
+            // zoom in
+            controller.zoom_by(0.5, Vec2::ZERO, &limits);
+        }
+
+        //block input during star spawning, reset positions and star count, send respawn event
+        if change_seed {
+            masterok.block_input = true;
+            masterok.partial_reset();
+            ev_change_seed.send(ChangeSeed);
+        }
+    }
+}
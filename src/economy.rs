@@ -0,0 +1,323 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::galaxy::{arm_rotations, arm_winding};
+use crate::naming::star_seed;
+use crate::resources::{GalaxyGenerated, GalaxySettings, Masterik, StarClass, StarData};
+
+//keeps resource rolls independent from names and planets of the same star
+const RESOURCE_SALT: u64 = 0x5245_534F_5552_4345;
+//stars inside this fraction of the galaxy radius are the core
+const CORE_FRACTION: f32 = 0.2;
+//the galaxy radius is taken at this percentile of distances, the stragglers past it are the halo
+const EDGE_PERCENTILE: f32 = 0.9;
+//share of the gap between two neighbouring arms that still counts as arm, half on either side of its middle
+const ARM_SHARE: f32 = 0.5;
+
+//where in the galaxy a star sits, old metal rich core, young arms, the quieter gaps between them
+//or the thin halo around it all
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GalacticRegion {
+    Core,
+    Arm,
+    InterArm,
+    Halo,
+}
+
+impl GalacticRegion {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GalacticRegion::Core => "Core",
+            GalacticRegion::Arm => "Arm",
+            GalacticRegion::InterArm => "Inter-Arm",
+            GalacticRegion::Halo => "Halo",
+        }
+    }
+
+    pub fn classify(position: Vec2, galaxy_radius: f32, settings: &GalaxySettings) -> Self {
+        let distance_from_core = position.length();
+        if distance_from_core < galaxy_radius * CORE_FRACTION {
+            return GalacticRegion::Core;
+        }
+        if distance_from_core > galaxy_radius {
+            return GalacticRegion::Halo;
+        }
+
+        //every arm crosses the line from the core through the star once per turn, the star is in an
+        //arm when one of those crossings is close, measured against the gap between neighbouring arms
+        let winding = arm_winding(settings).max(f32::EPSILON);
+        let rotations = arm_rotations(settings.spiral_arm_count);
+        let angle = position.y.atan2(position.x);
+        let nearest_arm = rotations
+            .iter()
+            .map(|rotation| {
+                let start = (angle - rotation).rem_euclid(TAU);
+                let turns = ((distance_from_core / winding - start) / TAU)
+                    .round()
+                    .max(0.0);
+                (distance_from_core - winding * (start + turns * TAU)).abs()
+            })
+            .fold(f32::MAX, f32::min);
+        let gap = winding * TAU / rotations.len() as f32;
+
+        if nearest_arm <= gap * ARM_SHARE * 0.5 {
+            GalacticRegion::Arm
+        } else {
+            GalacticRegion::InterArm
+        }
+    }
+
+    //(metals, gas, exotic, habitability) multipliers
+    fn modifiers(&self) -> (f32, f32, f32, f32) {
+        match self {
+            //crowded and irradiated, rich in heavy elements
+            GalacticRegion::Core => (1.4, 0.8, 1.6, 0.5),
+            //star forming gas clouds, but young hot stars and supernovae nearby
+            GalacticRegion::Arm => (1.0, 1.3, 0.9, 0.9),
+            //older and calmer, the gas has been used up
+            GalacticRegion::InterArm => (1.0, 0.8, 0.7, 1.2),
+            //old population, few heavy elements to build planets from
+            GalacticRegion::Halo => (0.5, 0.9, 1.2, 0.6),
+        }
+    }
+}
+
+//which value the heatmap shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResourceKind {
+    Metals,
+    Gas,
+    Exotic,
+    Habitability,
+}
+
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 4] = [
+        ResourceKind::Metals,
+        ResourceKind::Gas,
+        ResourceKind::Exotic,
+        ResourceKind::Habitability,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceKind::Metals => "Metals",
+            ResourceKind::Gas => "Gas",
+            ResourceKind::Exotic => "Exotic Matter",
+            ResourceKind::Habitability => "Habitability",
+        }
+    }
+}
+
+//all values are 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StarResources {
+    pub region: GalacticRegion,
+    pub metals: f32,
+    pub gas: f32,
+    pub exotic: f32,
+    pub habitability: f32,
+}
+
+//(metals, gas, exotic, habitability) before the region and the dice
+fn class_base(class: StarClass) -> (f32, f32, f32, f32) {
+    match class {
+        StarClass::O => (0.3, 0.9, 0.8, 0.0),
+        StarClass::B => (0.4, 0.8, 0.6, 0.05),
+        StarClass::A => (0.5, 0.6, 0.4, 0.2),
+        StarClass::F => (0.6, 0.5, 0.25, 0.6),
+        StarClass::G => (0.6, 0.5, 0.15, 0.9),
+        StarClass::K => (0.55, 0.45, 0.1, 0.75),
+        //long lived but flaring, the habitable zone is tidally locked
+        StarClass::M => (0.4, 0.3, 0.05, 0.35),
+    }
+}
+
+impl StarResources {
+    //the same galaxy seed and star index always give the same resources
+    pub fn generate(seed: u64, star: usize, class: StarClass, region: GalacticRegion) -> Self {
+        let mut rng = SmallRng::seed_from_u64(star_seed(seed, star) ^ RESOURCE_SALT);
+        let (metals, gas, exotic, habitability) = class_base(class);
+        let (metals_mod, gas_mod, exotic_mod, habitability_mod) = region.modifiers();
+        let mut roll =
+            |base: f32, modifier: f32| (base * modifier * rng.gen_range(0.6..1.4)).clamp(0.0, 1.0);
+
+        Self {
+            region,
+            metals: roll(metals, metals_mod),
+            gas: roll(gas, gas_mod),
+            exotic: roll(exotic, exotic_mod),
+            habitability: roll(habitability, habitability_mod),
+        }
+    }
+
+    pub fn get(&self, kind: ResourceKind) -> f32 {
+        match kind {
+            ResourceKind::Metals => self.metals,
+            ResourceKind::Gas => self.gas,
+            ResourceKind::Exotic => self.exotic,
+            ResourceKind::Habitability => self.habitability,
+        }
+    }
+}
+
+//resources of every star, indexed like Masterik.positions
+#[derive(Resource, Default)]
+pub struct StarEconomy {
+    pub galaxy_radius: f32,
+    pub stars: Vec<StarResources>,
+}
+
+impl StarEconomy {
+    pub fn build(
+        settings: &GalaxySettings,
+        positions: &[(f32, f32, f32)],
+        star_data: &StarData,
+    ) -> Self {
+        let mut distances: Vec<f32> = positions
+            .iter()
+            .map(|&(x, y, _)| Vec2::new(x, y).length())
+            .collect();
+        if distances.is_empty() {
+            return Self::default();
+        }
+        let edge = ((distances.len() - 1) as f32 * EDGE_PERCENTILE) as usize;
+        let galaxy_radius = *distances
+            .select_nth_unstable_by(edge, |a, b| a.total_cmp(b))
+            .1;
+
+        let stars = positions
+            .iter()
+            .enumerate()
+            .map(|(star, &(x, y, radius))| {
+                let region = GalacticRegion::classify(Vec2::new(x, y), galaxy_radius, settings);
+                StarResources::generate(
+                    settings.gen_seed,
+                    star,
+                    StarClass::from_radius(radius, star_data),
+                    region,
+                )
+            })
+            .collect();
+
+        Self {
+            galaxy_radius,
+            stars,
+        }
+    }
+
+    pub fn get(&self, star: usize) -> Option<&StarResources> {
+        self.stars.get(star)
+    }
+}
+
+//same triggers as the star grid, plus the class radii since they decide the classes
+pub fn rebuild_star_economy(
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut economy: ResMut<StarEconomy>,
+    mut last_len: Local<usize>,
+) {
+    let regenerated = ev_generated.read().count() > 0;
+    if masterok.block_input
+        || (!regenerated && !star_data.is_changed() && masterok.positions.len() == *last_len)
+    {
+        return;
+    }
+    *last_len = masterok.positions.len();
+    *economy = StarEconomy::build(&masterok.settings(), &masterok.positions, &star_data);
+}
+
+pub fn star_resources_ui(ui: &mut egui::Ui, resources: &StarResources) {
+    egui::CollapsingHeader::new("Resources")
+        .id_source("star_resources")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("star_resources_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Region");
+                    ui.label(resources.region.label());
+                    ui.end_row();
+
+                    for kind in ResourceKind::ALL {
+                        ui.label(kind.label());
+                        ui.add(
+                            egui::ProgressBar::new(resources.get(kind))
+                                .desired_width(120.0)
+                                .text(format!("{:.0}%", resources.get(kind) * 100.0)),
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> GalaxySettings {
+        GalaxySettings {
+            total_stars: 30000,
+            gen_seed: 1234,
+            spiral_arm_count: 2,
+            o_class: true,
+            b_class: true,
+            a_class: true,
+            f_class: true,
+            g_class: true,
+            k_class: true,
+            m_class: true,
+            angle_mod: 0.00076,
+            radius_mod: 2200.0,
+            distance_mod: 60.0,
+        }
+    }
+
+    //where the middle of the first arm is after this many radians
+    fn on_arm(settings: &GalaxySettings, angle: f32, turned_by: f32) -> Vec2 {
+        Vec2::from_angle(angle + turned_by) * arm_winding(settings) * angle
+    }
+
+    #[test]
+    fn tells_arms_from_the_gaps_between_them() {
+        let settings = settings();
+        let galaxy_radius = arm_winding(&settings) * 10.0;
+
+        for angle in [3.0, 5.5, 8.0] {
+            for rotation in arm_rotations(settings.spiral_arm_count) {
+                let star = on_arm(&settings, angle, *rotation);
+                assert_eq!(
+                    GalacticRegion::classify(star, galaxy_radius, &settings),
+                    GalacticRegion::Arm
+                );
+            }
+            //a quarter turn off lies halfway between the two arms
+            let star = on_arm(&settings, angle, std::f32::consts::FRAC_PI_2);
+            assert_eq!(
+                GalacticRegion::classify(star, galaxy_radius, &settings),
+                GalacticRegion::InterArm
+            );
+        }
+    }
+
+    #[test]
+    fn core_and_halo_go_by_distance() {
+        let settings = settings();
+        assert_eq!(
+            GalacticRegion::classify(Vec2::new(10.0, 0.0), 1000.0, &settings),
+            GalacticRegion::Core
+        );
+        assert_eq!(
+            GalacticRegion::classify(Vec2::new(0.0, 1500.0), 1000.0, &settings),
+            GalacticRegion::Halo
+        );
+    }
+}
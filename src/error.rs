@@ -0,0 +1,98 @@
+use std::fmt;
+use std::io;
+
+use bevy::ecs::query::QuerySingleError;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+//everything that can go wrong in the galaxy viewer without being worth a crash
+#[derive(Debug)]
+pub enum GalaxyError {
+    TerminalInit(io::Error),
+    TerminalDraw(io::Error),
+    Camera(QuerySingleError),
+    Clock(web_time::SystemTimeError),
+    Storage(io::Error),
+    Config(String),
+    Lanes(spade::InsertionError),
+    Save(String),
+    ShareCode(String),
+}
+
+impl fmt::Display for GalaxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GalaxyError::TerminalInit(err) => write!(f, "could not create info terminal: {}", err),
+            GalaxyError::TerminalDraw(err) => write!(f, "could not draw info terminal: {}", err),
+            GalaxyError::Camera(err) => write!(f, "galaxy camera unavailable: {}", err),
+            GalaxyError::Clock(err) => write!(f, "system clock is before 1970: {}", err),
+            GalaxyError::Storage(err) => write!(f, "storage error: {}", err),
+            GalaxyError::Config(err) => write!(f, "invalid config: {}", err),
+            GalaxyError::Lanes(err) => write!(f, "could not build star lanes: {}", err),
+            GalaxyError::Save(err) => write!(f, "unreadable save: {}", err),
+            GalaxyError::ShareCode(err) => write!(f, "invalid share code: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for GalaxyError {}
+
+//how long a toast stays on screen
+const TOAST_SECONDS: f32 = 6.0;
+
+pub struct Toast {
+    pub message: String,
+    pub remaining: f32,
+}
+
+//errors shown in the corner of the screen, repeated errors refresh the existing toast instead of stacking
+#[derive(Resource, Default)]
+pub struct ErrorToasts {
+    pub toasts: Vec<Toast>,
+}
+
+impl ErrorToasts {
+    //logs the error the first time it shows up and keeps it on screen while it keeps happening
+    pub fn report(&mut self, err: &GalaxyError) {
+        let message = err.to_string();
+
+        if let Some(toast) = self.toasts.iter_mut().find(|t| t.message == message) {
+            toast.remaining = TOAST_SECONDS;
+            return;
+        }
+
+        error!("{}", message);
+        self.toasts.push(Toast {
+            message,
+            remaining: TOAST_SECONDS,
+        });
+    }
+}
+
+pub fn error_toast_system(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    let delta = time.delta_seconds();
+    toasts.toasts.retain_mut(|toast| {
+        toast.remaining -= delta;
+        toast.remaining > 0.0
+    });
+
+    if toasts.toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("error_toasts"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10.0, -10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for toast in toasts.toasts.iter() {
+                egui::Frame::popup(ui.style())
+                    .fill(egui::Color32::from_rgb(60, 10, 10))
+                    .show(ui, |ui| {
+                        ui.colored_label(egui::Color32::LIGHT_RED, &toast.message);
+                    });
+            }
+        });
+}
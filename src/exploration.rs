@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::lanes::StarLanes;
+use crate::resources::{GalaxyGenerated, GalaxySettings, Masterik, StarClass, StarData, StarIndex};
+use crate::spatial::StarGrid;
+use crate::storage;
+
+//keeps the start star roll independent from everything else rolled from the galaxy seed
+const EXPLORATION_SALT: u64 = 0x4558_504C_4F52_4521;
+//sensors reach this many typical lane lengths around every visited star
+const SENSOR_LANES: f32 = 2.5;
+//alpha of unexplored stars when they are dimmed instead of hidden
+const DIM_ALPHA: f32 = 0.08;
+
+//the play state, saved per galaxy whenever it changes
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Exploration {
+    //seed of the galaxy this belongs to, None until a galaxy has been generated
+    #[serde(skip)]
+    pub galaxy: Option<u64>,
+    pub active: bool,
+    pub hide_unexplored: bool,
+    pub current: Option<usize>,
+    pub visited: Vec<usize>,
+}
+
+//what goes to disk, the settings make sure the indices still mean the same stars when loaded
+#[derive(Serialize, Deserialize)]
+struct SavedExploration {
+    settings: GalaxySettings,
+    exploration: Exploration,
+}
+
+//which stars the sensors have seen, derived from Exploration
+#[derive(Resource, Default)]
+pub struct ExploredStars {
+    pub active: bool,
+    pub hide_unexplored: bool,
+    pub sensor_radius: f32,
+    pub stars: Vec<bool>,
+    pub count: usize,
+}
+
+impl ExploredStars {
+    //outside of play mode everything counts as seen
+    pub fn reveals(&self, star: usize) -> bool {
+        !self.active || self.stars.get(star).copied().unwrap_or(false)
+    }
+}
+
+impl Exploration {
+    fn storage_key(seed: u64) -> String {
+        format!("exploration-{}", seed)
+    }
+
+    //a sun like star to start from, any star if the galaxy has none
+    pub fn start_star(
+        seed: u64,
+        positions: &[(f32, f32, f32)],
+        star_data: &StarData,
+    ) -> Option<usize> {
+        if positions.is_empty() {
+            return None;
+        }
+        let mut rng = SmallRng::seed_from_u64(seed ^ EXPLORATION_SALT);
+        let mut fallback = 0;
+        for _ in 0..200 {
+            let star = rng.gen_range(0..positions.len());
+            fallback = star;
+            if matches!(
+                StarClass::from_radius(positions[star].2, star_data),
+                StarClass::G | StarClass::K
+            ) {
+                return Some(star);
+            }
+        }
+        Some(fallback)
+    }
+
+    pub fn start(&mut self, star: usize) {
+        self.active = true;
+        self.current = Some(star);
+        self.visited = vec![star];
+    }
+
+    //only along lanes, and only to stars the sensors have already seen
+    pub fn can_jump(&self, to: usize, lanes: &StarLanes, explored: &ExploredStars) -> bool {
+        self.active
+            && explored.reveals(to)
+            && self
+                .current
+                .and_then(|current| lanes.adjacency.get(current))
+                .is_some_and(|neighbors| neighbors.contains(&to))
+    }
+
+    pub fn jump(&mut self, to: usize) {
+        self.current = Some(to);
+        if !self.visited.contains(&to) {
+            self.visited.push(to);
+        }
+    }
+
+    pub fn load(settings: &GalaxySettings, len: usize) -> Result<Option<Self>, GalaxyError> {
+        let Some(text) = storage::read_text(&Self::storage_key(settings.gen_seed))
+            .map_err(GalaxyError::Storage)?
+        else {
+            return Ok(None);
+        };
+        let saved: SavedExploration = ron::from_str(&text)
+            .map_err(|err| GalaxyError::Save(format!("exploration: {}", err)))?;
+
+        //a different galaxy under the same seed, the old progress does not apply
+        let valid = saved.exploration.visited.iter().all(|&star| star < len)
+            && saved.exploration.current.map_or(true, |star| star < len);
+        if saved.settings != *settings || !valid {
+            return Ok(None);
+        }
+        Ok(Some(saved.exploration))
+    }
+
+    pub fn save(&self, settings: GalaxySettings) -> Result<(), GalaxyError> {
+        let saved = SavedExploration {
+            settings,
+            exploration: self.clone(),
+        };
+        let text = ron::ser::to_string_pretty(&saved, Default::default())
+            .map_err(|err| GalaxyError::Save(format!("exploration: {}", err)))?;
+        storage::write_text(&Self::storage_key(saved.settings.gen_seed), &text)
+            .map_err(GalaxyError::Storage)
+    }
+}
+
+//every galaxy has its own progress, a freshly generated one picks up where it was left
+pub fn load_exploration_on_regenerate(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    masterok: Res<Masterik>,
+    mut exploration: ResMut<Exploration>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if ev_generated.read().count() == 0 {
+        return;
+    }
+
+    let loaded = match Exploration::load(&masterok.settings(), masterok.positions.len()) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            toasts.report(&err);
+            None
+        }
+    };
+    //loading is not a change, otherwise every generated galaxy would leave a file behind,
+    //the fog still follows since the grid is rebuilt for the new galaxy
+    *exploration.bypass_change_detection() = Exploration {
+        galaxy: Some(masterok.gen_seed),
+        //how the fog looks is a preference, not part of the progress
+        hide_unexplored: exploration.hide_unexplored,
+        ..loaded.unwrap_or_default()
+    };
+}
+
+pub fn save_exploration(
+    masterok: Res<Masterik>,
+    exploration: Res<Exploration>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if !exploration.is_changed()
+        || masterok.block_input
+        || exploration.galaxy != Some(masterok.gen_seed)
+    {
+        return;
+    }
+    if let Err(err) = exploration.save(masterok.settings()) {
+        toasts.report(&err);
+    }
+}
+
+pub fn update_explored_stars(
+    masterok: Res<Masterik>,
+    exploration: Res<Exploration>,
+    grid: Res<StarGrid>,
+    lanes: Res<StarLanes>,
+    mut explored: ResMut<ExploredStars>,
+) {
+    if masterok.block_input
+        || !(exploration.is_changed() || grid.is_changed() || lanes.is_changed())
+    {
+        return;
+    }
+
+    let sensor_radius = lanes.median_length * SENSOR_LANES;
+    let mut stars = vec![false; masterok.positions.len()];
+    for &visited in exploration.visited.iter() {
+        let Some(&(x, y, _)) = masterok.positions.get(visited) else {
+            continue;
+        };
+        for star in grid.within(&masterok.positions, Vec2::new(x, y), sensor_radius) {
+            stars[star] = true;
+        }
+        stars[visited] = true;
+    }
+
+    *explored = ExploredStars {
+        active: exploration.active,
+        hide_unexplored: exploration.hide_unexplored,
+        sensor_radius,
+        count: stars.iter().filter(|&&seen| seen).count(),
+        stars,
+    };
+}
+
+pub fn apply_fog_of_war(
+    explored: Res<ExploredStars>,
+    mut sprites: Query<(&StarIndex, &mut Sprite)>,
+) {
+    if !explored.is_changed() {
+        return;
+    }
+
+    for (&StarIndex(star), mut sprite) in sprites.iter_mut() {
+        let seen = explored.reveals(star);
+        let alpha = match (seen, explored.hide_unexplored) {
+            (true, _) => 1.0,
+            (false, true) => 0.0,
+            (false, false) => DIM_ALPHA,
+        };
+        //only write when it moved, every write re-uploads the sprite
+        if sprite.color.a() != alpha {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+//sensor range around where the ship is
+pub fn draw_sensor_range(
+    masterok: Res<Masterik>,
+    exploration: Res<Exploration>,
+    explored: Res<ExploredStars>,
+    mut gizmos: Gizmos,
+) {
+    let Some(&(x, y, _)) = exploration
+        .current
+        .filter(|_| exploration.active)
+        .and_then(|star| masterok.positions.get(star))
+    else {
+        return;
+    };
+    gizmos
+        .circle_2d(
+            Vec2::new(x, y),
+            explored.sensor_radius,
+            Color::rgba(0.3, 0.9, 1.0, 0.5),
+        )
+        .segments(64);
+}
+
+//returns a star to fly the camera to
+pub fn exploration_ui(
+    ui: &mut egui::Ui,
+    exploration: &mut Exploration,
+    explored: &ExploredStars,
+    selected: Option<usize>,
+    lanes: &StarLanes,
+    random_start: impl FnOnce() -> Option<usize>,
+    star_name: impl Fn(usize) -> String,
+) -> Option<usize> {
+    let mut fly_to = None;
+    egui::CollapsingHeader::new("Exploration")
+        .id_source("exploration")
+        .show(ui, |ui| {
+            if !exploration.active {
+                ui.horizontal(|ui| {
+                    if ui.button("Start Exploring").clicked() {
+                        if let Some(star) = selected.or_else(random_start) {
+                            exploration.start(star);
+                            fly_to = Some(star);
+                        }
+                    }
+                    if exploration.current.is_some() && ui.button("Resume").clicked() {
+                        exploration.active = true;
+                        fly_to = exploration.current;
+                    }
+                });
+                ui.small("Starts at the selected star, or a random one");
+                return;
+            }
+
+            if let Some(current) = exploration.current {
+                ui.label(format!("At {}", star_name(current)));
+            }
+            ui.label(format!(
+                "Explored {} of {} stars, visited {}",
+                explored.count,
+                explored.stars.len(),
+                exploration.visited.len()
+            ));
+
+            let target = selected.filter(|&star| exploration.can_jump(star, lanes, explored));
+            if ui
+                .add_enabled(target.is_some(), egui::Button::new("Jump to Selected"))
+                .clicked()
+            {
+                if let Some(star) = target {
+                    exploration.jump(star);
+                    fly_to = Some(star);
+                }
+            }
+            ui.small("Jumps follow lanes to stars already in sensor range");
+
+            ui.checkbox(&mut exploration.hide_unexplored, "Hide Unexplored");
+            ui.horizontal(|ui| {
+                if ui.button("Stop").clicked() {
+                    exploration.active = false;
+                }
+                if ui.button("Reset").clicked() {
+                    *exploration = Exploration {
+                        galaxy: exploration.galaxy,
+                        hide_unexplored: exploration.hide_unexplored,
+                        ..default()
+                    };
+                }
+            });
+        });
+    fly_to
+}
@@ -0,0 +1,272 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::lanes::StarLanes;
+use crate::naming::proper_name;
+use crate::resources::{GalaxyGenerated, Masterik, StarClass, StarData};
+
+//keeps capital picks independent from everything else rolled from the galaxy seed
+const FACTION_SALT: u64 = 0x4641_4354_494F_4E53;
+pub const MAX_FACTIONS: usize = 16;
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FactionSettings {
+    pub count: usize,
+    //capitals picked by hand, random stars fill the remaining slots
+    pub capitals: Vec<usize>,
+}
+
+impl Default for FactionSettings {
+    fn default() -> Self {
+        Self {
+            count: 6,
+            capitals: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Faction {
+    pub name: String,
+    pub capital: usize,
+    //srgb
+    pub color: [u8; 3],
+    //stronger factions claim stars further from home
+    pub strength: f32,
+    pub stars: usize,
+    //star count per class, hottest first like StarClass::ALL
+    pub classes: [usize; 7],
+}
+
+//who owns which star, owners is indexed like Masterik.positions
+#[derive(Resource, Default)]
+pub struct Territories {
+    pub factions: Vec<Faction>,
+    pub owners: Vec<Option<usize>>,
+}
+
+struct Frontier {
+    cost: f32,
+    star: usize,
+    faction: usize,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+fn faction_color(faction: usize, count: usize) -> [u8; 3] {
+    let hue = faction as f32 / count.max(1) as f32 * 360.0;
+    let [r, g, b, _] = Color::hsl(hue, 0.75, 0.55).as_rgba_u8();
+    [r, g, b]
+}
+
+impl Territories {
+    //weighted voronoi over the lane graph: every faction floods outwards from its capital and a star
+    //goes to whoever reaches it cheapest, lane length divided by strength
+    pub fn build(
+        seed: u64,
+        settings: &FactionSettings,
+        positions: &[(f32, f32, f32)],
+        star_data: &StarData,
+        lanes: &StarLanes,
+    ) -> Self {
+        if positions.is_empty() || lanes.adjacency.len() != positions.len() {
+            return Self::default();
+        }
+
+        let mut rng = SmallRng::seed_from_u64(seed ^ FACTION_SALT);
+        let count = settings.count.min(MAX_FACTIONS).min(positions.len());
+
+        let mut capitals: Vec<usize> = settings
+            .capitals
+            .iter()
+            .copied()
+            .filter(|&star| star < positions.len())
+            .take(count)
+            .collect();
+        //count never exceeds the number of stars, so this always finds enough distinct ones
+        while capitals.len() < count {
+            let star = rng.gen_range(0..positions.len());
+            if !capitals.contains(&star) {
+                capitals.push(star);
+            }
+        }
+
+        let mut factions: Vec<Faction> = capitals
+            .iter()
+            .enumerate()
+            .map(|(id, &capital)| Faction {
+                name: proper_name(seed ^ FACTION_SALT, capital),
+                capital,
+                color: faction_color(id, count),
+                strength: rng.gen_range(0.6..1.4),
+                stars: 0,
+                classes: [0; 7],
+            })
+            .collect();
+
+        let mut owners = vec![None; positions.len()];
+        let mut best_cost = vec![f32::INFINITY; positions.len()];
+        let mut frontier: BinaryHeap<Frontier> = capitals
+            .iter()
+            .enumerate()
+            .map(|(faction, &star)| Frontier {
+                cost: 0.0,
+                star,
+                faction,
+            })
+            .collect();
+
+        while let Some(Frontier {
+            cost,
+            star,
+            faction,
+        }) = frontier.pop()
+        {
+            if owners[star].is_some() {
+                continue;
+            }
+            owners[star] = Some(faction);
+
+            let (x, y, _) = positions[star];
+            for &next in &lanes.adjacency[star] {
+                if owners[next].is_some() {
+                    continue;
+                }
+                let (nx, ny, _) = positions[next];
+                let next_cost =
+                    cost + Vec2::new(x, y).distance(Vec2::new(nx, ny)) / factions[faction].strength;
+                if next_cost < best_cost[next] {
+                    best_cost[next] = next_cost;
+                    frontier.push(Frontier {
+                        cost: next_cost,
+                        star: next,
+                        faction,
+                    });
+                }
+            }
+        }
+
+        for (star, owner) in owners.iter().enumerate() {
+            if let Some(faction) = owner {
+                let class = StarClass::from_radius(positions[star].2, star_data);
+                let slot = StarClass::ALL.iter().position(|&c| c == class).unwrap_or(0);
+                factions[*faction].stars += 1;
+                factions[*faction].classes[slot] += 1;
+            }
+        }
+
+        Self { factions, owners }
+    }
+
+    pub fn owner(&self, star: usize) -> Option<&Faction> {
+        self.owners
+            .get(star)
+            .copied()
+            .flatten()
+            .and_then(|faction| self.factions.get(faction))
+    }
+}
+
+pub fn rebuild_territories(
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    settings: Res<FactionSettings>,
+    lanes: Res<StarLanes>,
+    mut territories: ResMut<Territories>,
+) {
+    if masterok.block_input || !(settings.is_changed() || lanes.is_changed()) {
+        return;
+    }
+    *territories = Territories::build(
+        masterok.gen_seed,
+        &settings,
+        &masterok.positions,
+        &star_data,
+        &lanes,
+    );
+}
+
+//hand picked capitals belong to the old galaxy
+pub fn clear_capitals_on_regenerate(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut settings: ResMut<FactionSettings>,
+) {
+    if ev_generated.read().count() > 0 && !settings.capitals.is_empty() {
+        settings.capitals.clear();
+    }
+}
+
+pub fn factions_ui(
+    ui: &mut egui::Ui,
+    settings: &mut FactionSettings,
+    territories: &Territories,
+    selected: Option<usize>,
+) {
+    egui::CollapsingHeader::new("Factions")
+        .id_source("factions")
+        .show(ui, |ui| {
+            ui.add(egui::Slider::new(&mut settings.count, 1..=MAX_FACTIONS).text("Factions"));
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        selected.is_some() && settings.capitals.len() < MAX_FACTIONS,
+                        egui::Button::new("Make Capital"),
+                    )
+                    .clicked()
+                {
+                    if let Some(star) = selected.filter(|star| !settings.capitals.contains(star)) {
+                        settings.capitals.push(star);
+                        settings.count = settings.count.max(settings.capitals.len());
+                    }
+                }
+                if ui
+                    .add_enabled(!settings.capitals.is_empty(), egui::Button::new("Random"))
+                    .clicked()
+                {
+                    settings.capitals.clear();
+                }
+            });
+
+            egui::Grid::new("factions_grid")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for faction in &territories.factions {
+                        let [r, g, b] = faction.color;
+                        ui.colored_label(egui::Color32::from_rgb(r, g, b), &faction.name);
+                        ui.label(format!("{} stars", faction.stars));
+                        let breakdown: Vec<String> = StarClass::ALL
+                            .iter()
+                            .zip(faction.classes)
+                            .filter(|(_, count)| *count > 0)
+                            .map(|(class, count)| format!("{}:{}", class.letter(), count))
+                            .collect();
+                        ui.small(breakdown.join(" "));
+                        ui.end_row();
+                    }
+                });
+        });
+}
@@ -0,0 +1,407 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::core_pipeline::bloom::BloomPrefilterSettings;
+use bevy::{
+    core_pipeline::{
+        bloom::{BloomCompositeMode, BloomSettings},
+        tonemapping::Tonemapping,
+    },
+    prelude::*,
+};
+
+use rand::Rng;
+
+use crate::camera::{CameraController, GalaxyCamera};
+use crate::resources::{
+    ChangeSeed, GalaxyGenerated, GalaxySettings, Masterik, RespawnStars, SpawnStars, StarCount,
+    StarData, StarIndex, StarsAdded, StarsRemoved,
+};
+
+//only used when the plugin owns the camera, embedding apps can bring their own GalaxyCamera
+pub fn spawn_galaxy_camera(mut commands: Commands) {
+    let bloom_set = BloomSettings {
+        intensity: 0.75,
+        low_frequency_boost: 0.5,
+        low_frequency_boost_curvature: 0.5,
+        high_pass_frequency: 1.0,
+        prefilter_settings: BloomPrefilterSettings {
+            threshold: 0.0,
+            threshold_softness: 0.0,
+        },
+        composite_mode: BloomCompositeMode::Additive,
+    };
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                hdr: true, // 1. HDR is required for bloom
+                ..default()
+            },
+            projection: OrthographicProjection {
+                far: 90000.0,
+                near: -90000.0,
+                scale: 1400.0,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface, // 2. Using a tonemapper that desaturates to white is recommended
+            ..default()
+        },
+        bloom_set, // 3. Enable bloom for the camera
+        CameraController::new(1400.0),
+        GalaxyCamera,
+    ));
+}
+
+//the star sprite embedded in the binary, shared by everything that draws stars or planets
+pub fn star_texture(asset_server: &AssetServer) -> Handle<Image> {
+    let path = std::path::Path::new("spiral_galaxy").join("star.png");
+    let source = bevy::asset::io::AssetSourceId::from("embedded");
+    asset_server.load(bevy::asset::AssetPath::from_path(&path).with_source(source))
+}
+
+pub fn request_initial_stars(mut ev_respawn: EventWriter<RespawnStars>) {
+    // Send event to spawn stars
+    ev_respawn.send(RespawnStars);
+}
+
+//how far each arm is turned, generate_star_positions_in_range flips and rotates stars by their index to get these
+pub fn arm_rotations(spiral_arm_count: i64) -> &'static [f32] {
+    const ROTATIONS: [f32; 4] = [0.0, PI, -FRAC_PI_2, FRAC_PI_2];
+    &ROTATIONS[..spiral_arm_count.clamp(1, 4) as usize]
+}
+
+//distance from the core gained per radian along an arm, the middle of the radius roll below
+pub fn arm_winding(settings: &GalaxySettings) -> f32 {
+    1.5 * settings.radius_mod * settings.distance_mod
+}
+
+pub fn generate_star_positions_in_range(
+    start: i64,
+    end: i64,
+    masterok: &mut Masterik,
+    star_data: &StarData,
+) {
+    let rand_range = 20000.0_f32;
+    for mut star_index in start..end {
+        //this keeps stars closer to center when spawning more stars, since the spawning alternates arms
+        star_index /= masterok.spiral_arm_count;
+        //randomness to make it look natural
+        let random_angle: f32 = masterok.rng.gen_range(0.0..masterok.angle_mod);
+
+        let angle = star_index as f32 * (0.0002 + random_angle);
+
+        let random_radius: f32 = masterok.rng.gen_range(2.0..masterok.radius_mod);
+        let radius = (masterok.radius_mod + random_radius) * angle;
+        let mut xik = radius * angle.cos() * masterok.distance_mod;
+        let mut yik = radius * angle.sin() * masterok.distance_mod;
+
+        let random_star = masterok.rng.gen_range(0..1000000);
+
+        let spawning_radius = if random_star > star_data.k_class_rarity && masterok.m_class {
+            star_data.m_class_radius
+        } else if random_star > star_data.g_class_rarity && masterok.k_class {
+            star_data.k_class_radius
+        } else if random_star > star_data.f_class_rarity && masterok.g_class {
+            star_data.g_class_radius
+        } else if random_star > star_data.a_class_rarity && masterok.f_class {
+            star_data.f_class_radius
+        } else if random_star > star_data.b_class_rarity && masterok.a_class {
+            star_data.a_class_radius
+        } else if random_star > star_data.o_class_rarity && masterok.b_class {
+            star_data.b_class_radius
+        } else if masterok.o_class {
+            star_data.o_class_radius
+        } else {
+            70.0
+        };
+
+        let random_offset_x: f32 = masterok.rng.gen_range(-rand_range..rand_range);
+        let random_offset_y: f32 = masterok.rng.gen_range(-rand_range..rand_range);
+
+        xik += random_offset_x;
+        yik += random_offset_y;
+
+        //this creates the spiral arms
+        if star_index % 5 == 0 && masterok.spiral_arm_count > 3 {
+            let holder = yik;
+            yik = xik;
+            xik = -holder;
+        } else if star_index % 3 == 0 && masterok.spiral_arm_count > 2 {
+            let holder = xik;
+            xik = yik;
+            yik = -holder;
+        } else if star_index % 2 == 0 && masterok.spiral_arm_count > 1 {
+            xik = -xik;
+            yik = -yik;
+        }
+
+        // Ensure the new circle does not overlap with any existing circles
+        let mut attempts = 0;
+        while masterok.positions.iter().any(|&(px, py, checking_radius)| {
+            let dx = xik - px;
+            let dy = yik - py;
+            (((dx * dx) + (dy * dy)) as f64).sqrt() < (checking_radius + spawning_radius) as f64
+        }) && attempts < 20
+        {
+            xik += masterok.rng.gen_range(-50000.0..50000.0);
+            yik += masterok.rng.gen_range(-50000.0..50000.0);
+            attempts += 1;
+        }
+
+        if attempts < 19 {
+            // Store the new circle position
+            masterok.positions.push((xik, yik, spawning_radius));
+        }
+    }
+}
+
+pub fn star_color_from_radius(radius: &f32, star_data: &StarData) -> Color {
+    let test_radius = radius + 10.0;
+
+    if test_radius > star_data.b_class_radius {
+        Color::rgb_u8(5, 5, 250)
+    } else if test_radius > star_data.a_class_radius {
+        Color::rgb_u8(10, 10, 240)
+    } else if test_radius > star_data.f_class_radius {
+        Color::rgb_u8(250, 250, 250)
+    } else if test_radius > star_data.g_class_radius {
+        Color::rgb_u8(200, 100, 100)
+    } else if test_radius > star_data.k_class_radius {
+        Color::rgb_u8(254, 170, 52)
+    } else {
+        Color::rgb_u8(30, 0, 0)
+    }
+}
+
+pub fn spawn_initial_stars(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut masterok: ResMut<Masterik>,
+    star_data: Res<StarData>,
+    mut ev_respawn: EventReader<RespawnStars>,
+    mut ev_generated: EventWriter<GalaxyGenerated>,
+) {
+    for _ in ev_respawn.read() {
+        generate_star_positions_in_range(1, masterok.total_stars, &mut masterok, &star_data);
+
+        let mut initial_counter = 0;
+
+        let star = star_texture(&asset_server);
+
+        for (x, y, radius) in &masterok.positions {
+            initial_counter += 1;
+
+            let radius = *radius;
+            let test_radius = radius + 10.0;
+
+            let star_color = star_color_from_radius(&test_radius, &star_data);
+
+            let mut transform = Transform::from_translation(Vec3::new(*x, *y, 0.));
+            //rotate stars a bit to make it look more natural
+            transform.rotate_local_z(*x);
+            commands.spawn((
+                SpriteBundle {
+                    texture: star.clone(),
+                    transform,
+                    sprite: Sprite {
+                        color: star_color,
+                        custom_size: Some(Vec2::splat(radius * 2.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                StarCount(initial_counter),
+                StarIndex(initial_counter as usize - 1),
+            ));
+        }
+
+        //spawns stars that act as backdrop of the galaxy
+        for randomczik in 1..20000 {
+            // initial_counter += 1;
+
+            let spawning_radius: f32 = masterok
+                .rng
+                .gen_range(10.0..(star_data.k_class_radius + 100.0));
+
+            let rand_range = randomczik as f32 * 90.0;
+
+            let mut random_offset_x: f32 = masterok.rng.gen_range(-rand_range..rand_range);
+            let mut random_offset_y: f32 = masterok.rng.gen_range(-rand_range..rand_range);
+
+            let radius = spawning_radius;
+            //buffer radius for testing to avoid floating point errors
+            let test_radius = radius + 10.0;
+
+            let star_color = star_color_from_radius(&test_radius, &star_data);
+
+            // Ensure the new circle does not overlap with any existing circles
+            let mut attempts = 0;
+            while masterok.positions.iter().any(|&(px, py, checking_radius)| {
+                let dx = random_offset_x - px;
+                let dy = random_offset_y - py;
+                (((dx * dx) + (dy * dy)) as f64).sqrt() < (checking_radius + radius) as f64
+            }) && attempts < 10
+            {
+                random_offset_x += masterok.rng.gen_range(-radius..radius);
+                random_offset_y += masterok.rng.gen_range(-radius..radius);
+                attempts += 1;
+            }
+
+            if attempts < 9 {
+                let index = masterok.positions.len();
+                // Store the new circle position
+                masterok
+                    .positions
+                    .push((random_offset_x, random_offset_y, radius));
+
+                let mut transform =
+                    Transform::from_translation(Vec3::new(random_offset_x, random_offset_y, 0.));
+                //rotate stars a bit to make it look more natural
+                transform.rotate_local_z(random_offset_x);
+
+                commands.spawn((
+                    SpriteBundle {
+                        texture: star.clone(),
+                        transform,
+                        sprite: Sprite {
+                            color: star_color, // 4. Put something bright in a dark environment to see the effect
+                            custom_size: Some(Vec2::splat(radius * 2.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    StarCount(11),
+                    StarIndex(index),
+                ));
+            }
+        }
+        masterok.block_input = false;
+        ev_generated.send(GalaxyGenerated {
+            seed: masterok.gen_seed,
+            star_count: masterok.positions.len(),
+        });
+    }
+}
+
+pub fn star_watcher(
+    mut ev_spawn_stars: EventReader<SpawnStars>,
+    mut masterok: ResMut<Masterik>,
+    mut ev_stars_add: EventWriter<StarsAdded>,
+    mut ev_stars_remove: EventWriter<StarsRemoved>,
+) {
+    for ev in ev_spawn_stars.read() {
+        let previous_value = masterok.total_stars;
+
+        let potential_value = masterok.total_stars + ev.0;
+
+        if potential_value > 0 && potential_value < 301000 {
+            masterok.total_stars += ev.0;
+
+            //removing stars
+            if ev.0 < 0 {
+                ev_stars_remove.send(StarsRemoved(previous_value));
+            } else
+            //adding stars
+            {
+                ev_stars_add.send(StarsAdded(previous_value));
+            }
+        }
+    }
+}
+
+pub fn star_adder(
+    mut masterok: ResMut<Masterik>,
+    star_data: Res<StarData>,
+    mut ev_stars_add: EventReader<StarsAdded>,
+    mut commands: Commands,
+
+    asset_server: Res<AssetServer>,
+) {
+    for ev in ev_stars_add.read() {
+        let previous_value = ev.0;
+
+        let new_value = masterok.total_stars;
+
+        if new_value > previous_value {
+            let amount_added = new_value - previous_value;
+
+            generate_star_positions_in_range(previous_value, new_value, &mut masterok, &star_data);
+
+            let mut positions_clone = masterok.positions.clone();
+
+            let star = star_texture(&asset_server);
+
+            for new_star in 0..amount_added {
+                let Some((x, y, radius)) = positions_clone.pop() else {
+                    break;
+                };
+                let index = positions_clone.len();
+
+                let test_radius = radius + 10.0;
+
+                let star_color = star_color_from_radius(&test_radius, &star_data);
+
+                let mut transform = Transform::from_translation(Vec3::new(x, y, 0.));
+                transform.rotate_local_z(x);
+
+                commands.spawn((
+                    SpriteBundle {
+                        texture: star.clone(),
+                        transform,
+                        sprite: Sprite {
+                            color: star_color, // 4. Put something bright in a dark environment to see the effect
+                            custom_size: Some(Vec2::splat(radius * 2.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    StarCount(previous_value + new_star),
+                    StarIndex(index),
+                ));
+            }
+        }
+    }
+}
+
+pub fn star_remover(
+    mut masterok: ResMut<Masterik>,
+
+    mut ev_stars_remove: EventReader<StarsRemoved>,
+
+    mut commands: Commands,
+    query: Query<(Entity, &StarCount)>,
+) {
+    for ev in ev_stars_remove.read() {
+        let previous_value = ev.0;
+
+        let new_value = masterok.total_stars;
+
+        let amount_remove = previous_value - new_value;
+
+        if amount_remove > 0 {
+            for _ in 1..amount_remove {
+                masterok.positions.pop();
+            }
+
+            for (entity, sc) in query.iter() {
+                if sc.0 > new_value {
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+    }
+}
+
+pub fn despawn_all_stars(
+    mut ev_change_seed: EventReader<ChangeSeed>,
+    mut ev_respawn: EventWriter<RespawnStars>,
+
+    mut commands: Commands,
+    query: Query<Entity, With<StarCount>>,
+) {
+    for _ in ev_change_seed.read() {
+        for entity in query.iter() {
+            commands.entity(entity).despawn();
+        }
+        ev_respawn.send(RespawnStars);
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::attract::AttractMode;
+use crate::input::{Action, ActionState};
+use crate::resources::{ChangeSeed, GalaxyGenerated, GalaxySettings, Masterik, SpawnStars};
+
+//how many snapshots we keep before dropping the oldest
+const MAX_HISTORY: usize = 100;
+
+//a generated galaxy and the stars added or removed by hand on top of it
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub settings: GalaxySettings,
+    //star count changes in the order they were made, adding draws from the rng so the order matters
+    pub steps: Vec<i64>,
+}
+
+impl HistoryEntry {
+    pub fn total_stars(&self) -> i64 {
+        self.settings.total_stars + self.steps.iter().sum::<i64>()
+    }
+}
+
+//undo/redo stack of galaxies, cursor points at the galaxy currently on screen
+#[derive(Resource, Default)]
+pub struct SettingsHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub cursor: usize,
+    //steps of the entry jumped to that still have to be sent once its galaxy is regenerated
+    replay: VecDeque<i64>,
+}
+
+impl SettingsHistory {
+    //a freshly generated galaxy starts a new entry, a change of the star count alone is a step on the
+    //current one, pushes unless it is the entry we are already at and drops any redo entries
+    pub fn record(&mut self, settings: GalaxySettings, generated: bool) {
+        let entry = match self.entries.get(self.cursor) {
+            Some(current)
+                if !generated
+                    && current.settings
+                        == GalaxySettings {
+                            total_stars: current.settings.total_stars,
+                            ..settings.clone()
+                        } =>
+            {
+                let step = settings.total_stars - current.total_stars();
+                if step == 0 {
+                    return;
+                }
+                let mut steps = current.steps.clone();
+                steps.push(step);
+                HistoryEntry {
+                    settings: current.settings.clone(),
+                    steps,
+                }
+            }
+            _ => HistoryEntry {
+                settings,
+                steps: Vec::new(),
+            },
+        };
+        if self.entries.get(self.cursor) == Some(&entry) {
+            return;
+        }
+
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.cursor + 1);
+        }
+        self.entries.push(entry);
+
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.cursor = self.entries.len() - 1;
+    }
+
+    pub fn undo(&mut self) -> Option<GalaxySettings> {
+        if self.cursor == 0 || self.entries.is_empty() {
+            return None;
+        }
+        self.jump(self.cursor - 1)
+    }
+
+    pub fn redo(&mut self) -> Option<GalaxySettings> {
+        self.jump(self.cursor + 1)
+    }
+
+    //returns the settings to regenerate from and queues the steps to replay on top
+    pub fn jump(&mut self, index: usize) -> Option<GalaxySettings> {
+        let entry = self.entries.get(index)?;
+        self.replay = entry.steps.iter().copied().collect();
+        self.cursor = index;
+        Some(entry.settings.clone())
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        !self.replay.is_empty()
+    }
+}
+
+//records the galaxy once it has finished spawning, the random galaxies of attract mode are not the
+//player's and stay out of it, and so do the steps of an entry being replayed
+pub fn record_settings_history(
+    masterok: Res<Masterik>,
+    attract: Option<Res<AttractMode>>,
+    mut history: ResMut<SettingsHistory>,
+    mut ev_generated: EventReader<GalaxyGenerated>,
+) {
+    let generated = ev_generated.read().count() > 0;
+    if !masterok.block_input
+        && !history.is_replaying()
+        && !attract.is_some_and(|attract| attract.is_running())
+    {
+        history.record(masterok.settings(), generated);
+    }
+}
+
+//adds and removes the stars of the entry jumped to, one step a frame so star_adder sees each count
+//change on its own and draws the same positions from the rng as the first time
+pub fn replay_history_steps(
+    masterok: Res<Masterik>,
+    mut history: ResMut<SettingsHistory>,
+    mut ev_spawn_stars: EventWriter<SpawnStars>,
+) {
+    if masterok.block_input {
+        return;
+    }
+    if let Some(step) = history.replay.pop_front() {
+        ev_spawn_stars.send(SpawnStars(step));
+    }
+}
+
+//undo / redo walk through the history and regenerate the galaxy
+pub fn history_input_system(
+    actions: Res<ActionState>,
+    mut masterok: ResMut<Masterik>,
+    mut history: ResMut<SettingsHistory>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+) {
+    if masterok.block_input || history.is_replaying() {
+        return;
+    }
+
+    let restored = if actions.just_pressed(Action::Undo) {
+        history.undo()
+    } else if actions.just_pressed(Action::Redo) {
+        history.redo()
+    } else {
+        None
+    };
+
+    if let Some(settings) = restored {
+        masterok.apply_settings(&settings);
+        ev_change_seed.send(ChangeSeed);
+    }
+}
+
+//list of earlier galaxies, returns the entry that got clicked
+pub fn history_list_ui(ui: &mut egui::Ui, history: &SettingsHistory) -> Option<usize> {
+    let mut clicked = None;
+
+    ui.label(egui::RichText::new("History").color(egui::Color32::GRAY));
+    egui::ScrollArea::vertical()
+        .id_source("settings_history")
+        .max_height(160.0)
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for (index, entry) in history.entries.iter().enumerate() {
+                let text = format!(
+                    "#{} seed {} | arms {} | stars {}",
+                    index + 1,
+                    entry.settings.gen_seed,
+                    entry.settings.spiral_arm_count,
+                    entry.total_stars()
+                );
+                if ui.selectable_label(index == history.cursor, text).clicked() {
+                    clicked = Some(index);
+                }
+            }
+        });
+
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(gen_seed: u64, total_stars: i64) -> GalaxySettings {
+        GalaxySettings {
+            total_stars,
+            gen_seed,
+            spiral_arm_count: 2,
+            o_class: true,
+            b_class: true,
+            a_class: true,
+            f_class: true,
+            g_class: true,
+            k_class: true,
+            m_class: true,
+            angle_mod: 0.0007,
+            radius_mod: 2500.0,
+            distance_mod: 100.0,
+        }
+    }
+
+    #[test]
+    fn star_count_changes_are_steps_on_the_galaxy() {
+        let mut history = SettingsHistory::default();
+        history.record(settings(1, 20000), true);
+        history.record(settings(1, 21000), false);
+        history.record(settings(1, 11000), false);
+
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.entries[2].settings, settings(1, 20000));
+        assert_eq!(history.entries[2].steps, vec![1000, -10000]);
+        assert_eq!(history.entries[2].total_stars(), 11000);
+
+        //regenerating starts over, even with the same settings
+        history.record(settings(1, 11000), true);
+        assert_eq!(history.entries.len(), 4);
+        assert!(history.entries[3].steps.is_empty());
+    }
+
+    #[test]
+    fn jumping_queues_the_steps_to_replay() {
+        let mut history = SettingsHistory::default();
+        history.record(settings(1, 20000), true);
+        history.record(settings(1, 21000), false);
+        history.record(settings(1, 31000), false);
+        history.record(settings(2, 20000), true);
+
+        assert_eq!(history.undo(), Some(settings(1, 20000)));
+        assert_eq!(
+            history.replay.iter().copied().collect::<Vec<_>>(),
+            vec![1000, 10000]
+        );
+
+        //once replayed the galaxy matches the entry and nothing new gets recorded
+        history.replay.clear();
+        history.record(settings(1, 31000), false);
+        assert_eq!(history.entries.len(), 4);
+        assert_eq!(history.cursor, 2);
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::resources::{StarClass, UiFocus};
+use crate::storage;
+
+//everything the player can do from a keyboard, systems read these instead of raw keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ZoomOut,
+    ZoomIn,
+    ChangeSeed,
+    ResetToDefault,
+    Undo,
+    Redo,
+    AddStars1000,
+    RemoveStars1000,
+    AddStars10000,
+    RemoveStars10000,
+    AddArm,
+    RemoveArm,
+    AddAngle,
+    RemoveAngle,
+    AddRadius,
+    RemoveRadius,
+    AddDistance,
+    RemoveDistance,
+    ToggleClass(StarClass),
+    EnterSystem,
+    LeaveSystem,
+    TogglePanel,
+    //jumps to the bookmark at this index
+    Bookmark(u8),
+    RecordCameraPath,
+    PlayCameraPath,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub key: KeyCode,
+    #[serde(default)]
+    pub ctrl: bool,
+}
+
+impl KeyBinding {
+    pub const fn key(key: KeyCode) -> Self {
+        Self { key, ctrl: false }
+    }
+
+    pub const fn ctrl(key: KeyCode) -> Self {
+        Self { key, ctrl: true }
+    }
+
+    pub fn label(&self) -> String {
+        let key = key_name(self.key);
+        if self.ctrl {
+            format!("Ctrl+{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+//short printable name for the help text
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Semicolon => ";".into(),
+        KeyCode::Comma => ",".into(),
+        KeyCode::Period => ".".into(),
+        KeyCode::Slash => "/".into(),
+        KeyCode::Quote => "'".into(),
+        KeyCode::BracketLeft => "[".into(),
+        KeyCode::BracketRight => "]".into(),
+        KeyCode::Minus => "-".into(),
+        KeyCode::Equal => "=".into(),
+        KeyCode::Backquote => "`".into(),
+        KeyCode::Backspace => "Bksp".into(),
+        KeyCode::ArrowUp => "Up".into(),
+        KeyCode::ArrowDown => "Down".into(),
+        KeyCode::ArrowLeft => "Left".into(),
+        KeyCode::ArrowRight => "Right".into(),
+        other => {
+            let name = format!("{:?}", other);
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .map(String::from)
+                .unwrap_or(name)
+        }
+    }
+}
+
+//active bindings, an action can have several keys
+#[derive(Resource, Clone)]
+pub struct KeyMap {
+    pub bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use KeyCode::*;
+
+        let mut bindings = HashMap::new();
+        let mut bind = |action, keys: &[KeyBinding]| {
+            bindings.insert(action, keys.to_vec());
+        };
+
+        bind(Action::MoveUp, &[KeyBinding::key(KeyW)]);
+        bind(Action::MoveDown, &[KeyBinding::key(KeyS)]);
+        bind(Action::MoveLeft, &[KeyBinding::key(KeyA)]);
+        bind(Action::MoveRight, &[KeyBinding::key(KeyD)]);
+        bind(Action::ZoomOut, &[KeyBinding::key(KeyQ)]);
+        bind(Action::ZoomIn, &[KeyBinding::key(KeyE)]);
+        bind(Action::ChangeSeed, &[KeyBinding::key(KeyR)]);
+        bind(Action::ResetToDefault, &[KeyBinding::key(KeyF)]);
+        bind(Action::Undo, &[KeyBinding::ctrl(KeyZ)]);
+        bind(Action::Redo, &[KeyBinding::ctrl(KeyY)]);
+        bind(Action::AddStars1000, &[KeyBinding::key(KeyI)]);
+        bind(Action::RemoveStars1000, &[KeyBinding::key(KeyK)]);
+        bind(Action::AddStars10000, &[KeyBinding::key(KeyO)]);
+        bind(Action::RemoveStars10000, &[KeyBinding::key(KeyL)]);
+        bind(Action::AddArm, &[KeyBinding::key(KeyP)]);
+        bind(Action::RemoveArm, &[KeyBinding::key(Semicolon)]);
+        bind(Action::AddAngle, &[KeyBinding::key(KeyT)]);
+        bind(Action::RemoveAngle, &[KeyBinding::key(KeyG)]);
+        bind(Action::AddRadius, &[KeyBinding::key(KeyY)]);
+        bind(Action::RemoveRadius, &[KeyBinding::key(KeyH)]);
+        bind(Action::AddDistance, &[KeyBinding::key(KeyU)]);
+        bind(Action::RemoveDistance, &[KeyBinding::key(KeyJ)]);
+        bind(Action::ToggleClass(StarClass::O), &[KeyBinding::key(KeyZ)]);
+        bind(Action::ToggleClass(StarClass::B), &[KeyBinding::key(KeyX)]);
+        bind(Action::ToggleClass(StarClass::A), &[KeyBinding::key(KeyC)]);
+        bind(Action::ToggleClass(StarClass::F), &[KeyBinding::key(KeyV)]);
+        bind(Action::ToggleClass(StarClass::G), &[KeyBinding::key(KeyB)]);
+        bind(Action::ToggleClass(StarClass::K), &[KeyBinding::key(KeyN)]);
+        bind(Action::ToggleClass(StarClass::M), &[KeyBinding::key(KeyM)]);
+        bind(Action::EnterSystem, &[KeyBinding::key(Enter)]);
+        bind(Action::LeaveSystem, &[KeyBinding::key(Escape)]);
+        bind(Action::TogglePanel, &[KeyBinding::key(Backquote)]);
+        bind(Action::RecordCameraPath, &[KeyBinding::key(BracketLeft)]);
+        bind(Action::PlayCameraPath, &[KeyBinding::key(BracketRight)]);
+        let digits = [
+            Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        ];
+        for (index, digit) in digits.into_iter().enumerate() {
+            bind(Action::Bookmark(index as u8), &[KeyBinding::key(digit)]);
+        }
+        bind(
+            Action::Quit,
+            &[KeyBinding::key(Backspace), KeyBinding::key(Delete)],
+        );
+
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    //storage key of the user overrides file
+    pub const STORAGE_KEY: &'static str = "keybindings";
+
+    //default map with the user overrides from the config file on top
+    pub fn load() -> Result<Self, GalaxyError> {
+        let mut keymap = KeyMap::default();
+
+        if let Some(text) = storage::read_text(Self::STORAGE_KEY).map_err(GalaxyError::Storage)? {
+            let overrides: HashMap<Action, Vec<KeyBinding>> = ron::from_str(&text)
+                .map_err(|err| GalaxyError::Config(format!("key bindings: {}", err)))?;
+            keymap.bindings.extend(overrides);
+        }
+
+        Ok(keymap)
+    }
+
+    //first binding of an action for the help text, "-" if it is unbound
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .and_then(|keys| keys.first())
+            .map(KeyBinding::label)
+            .unwrap_or_else(|| "-".into())
+    }
+
+    //"[A/B]" style label for a pair of actions
+    pub fn pair(&self, first: Action, second: Action) -> String {
+        format!("[{}/{}]", self.label(first), self.label(second))
+    }
+}
+
+//an unreadable overrides file leaves the default bindings in place
+pub fn load_key_map(mut keymap: ResMut<KeyMap>, mut toasts: ResMut<ErrorToasts>) {
+    match KeyMap::load() {
+        Ok(loaded) => *keymap = loaded,
+        Err(err) => toasts.report(&err),
+    }
+}
+
+//controller buttons per action, sticks and triggers are fixed to pan and zoom
+#[derive(Resource, Clone)]
+pub struct GamepadMap {
+    pub buttons: HashMap<Action, Vec<GamepadButtonType>>,
+    pub pan_stick: (GamepadAxisType, GamepadAxisType),
+    pub zoom_in_trigger: GamepadButtonType,
+    pub zoom_out_trigger: GamepadButtonType,
+}
+
+impl Default for GamepadMap {
+    fn default() -> Self {
+        use GamepadButtonType::*;
+
+        let mut buttons = HashMap::new();
+        buttons.insert(Action::ChangeSeed, vec![South]);
+        buttons.insert(Action::AddStars1000, vec![West]);
+        buttons.insert(Action::AddStars10000, vec![North]);
+        buttons.insert(Action::RemoveStars1000, vec![East]);
+        buttons.insert(Action::AddArm, vec![DPadUp]);
+        buttons.insert(Action::RemoveArm, vec![DPadDown]);
+        buttons.insert(Action::ZoomIn, vec![RightTrigger]);
+        buttons.insert(Action::ZoomOut, vec![LeftTrigger]);
+        buttons.insert(Action::ResetToDefault, vec![Select]);
+
+        Self {
+            buttons,
+            pan_stick: (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            zoom_in_trigger: RightTrigger2,
+            zoom_out_trigger: LeftTrigger2,
+        }
+    }
+}
+
+//what the bound keys and buttons did this frame
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    //analog stick deflection, -1..1 on both axes with y up
+    pub pan: Vec2,
+    //positive zooms in, negative zooms out, -1..1
+    pub zoom: f32,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    //nothing later in the frame sees any action, for whatever grabbed the keyboard first
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+//ctrl has to match exactly, so ctrl+z does not also toggle o-class stars
+pub fn update_action_state(
+    input: Res<ButtonInput<KeyCode>>,
+    keymap: Res<KeyMap>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_triggers: Res<Axis<GamepadButton>>,
+    gamepad_map: Res<GamepadMap>,
+    focus: Res<UiFocus>,
+    mut state: ResMut<ActionState>,
+) {
+    let ctrl_held = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    state.pressed.clear();
+    state.just_pressed.clear();
+
+    //keys typed into the seed or search box belong to egui
+    for (action, keys) in keymap.bindings.iter().filter(|_| !focus.keyboard_over_ui) {
+        for binding in keys.iter().filter(|binding| binding.ctrl == ctrl_held) {
+            if input.pressed(binding.key) {
+                state.pressed.insert(*action);
+            }
+            if input.just_pressed(binding.key) {
+                state.just_pressed.insert(*action);
+            }
+        }
+    }
+
+    state.pan = Vec2::ZERO;
+    state.zoom = 0.0;
+
+    //every connected controller drives the same actions, deadzones come from bevy's GamepadSettings
+    for gamepad in gamepads.iter() {
+        for (action, buttons) in gamepad_map.buttons.iter() {
+            for button_type in buttons {
+                let button = GamepadButton::new(gamepad, *button_type);
+                if gamepad_buttons.pressed(button) {
+                    state.pressed.insert(*action);
+                }
+                if gamepad_buttons.just_pressed(button) {
+                    state.just_pressed.insert(*action);
+                }
+            }
+        }
+
+        let (axis_x, axis_y) = gamepad_map.pan_stick;
+        let stick = Vec2::new(
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_x))
+                .unwrap_or(0.0),
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_y))
+                .unwrap_or(0.0),
+        );
+        state.pan += stick;
+
+        let zoom_in = gamepad_triggers
+            .get(GamepadButton::new(gamepad, gamepad_map.zoom_in_trigger))
+            .unwrap_or(0.0);
+        let zoom_out = gamepad_triggers
+            .get(GamepadButton::new(gamepad, gamepad_map.zoom_out_trigger))
+            .unwrap_or(0.0);
+        state.zoom += zoom_in - zoom_out;
+    }
+
+    state.pan = state.pan.clamp_length_max(1.0);
+    state.zoom = state.zoom.clamp(-1.0, 1.0);
+}
@@ -0,0 +1,540 @@
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use spade::handles::VertexHandle;
+use spade::{DelaunayTriangulation, HasPosition, Point2, Triangulation};
+
+use crate::camera::GalaxyCamera;
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::resources::{GalaxyGenerated, Masterik};
+use crate::system_view::ViewMode;
+
+//lanes fade in between these on screen lengths of a typical lane, in pixels
+const FADE_START_PIXELS: f32 = 4.0;
+const FADE_FULL_PIXELS: f32 = 24.0;
+const LANE_ALPHA: f32 = 0.6;
+
+//which delaunay edges survive, each option is a subset of the one before
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LanePruning {
+    Delaunay,
+    Gabriel,
+    #[default]
+    RelativeNeighborhood,
+}
+
+impl LanePruning {
+    pub const ALL: [LanePruning; 3] = [
+        LanePruning::Delaunay,
+        LanePruning::Gabriel,
+        LanePruning::RelativeNeighborhood,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LanePruning::Delaunay => "Delaunay",
+            LanePruning::Gabriel => "Gabriel",
+            LanePruning::RelativeNeighborhood => "Relative Neighborhood",
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaneSettings {
+    pub visible: bool,
+    pub pruning: LanePruning,
+    //lanes longer than this many times the median delaunay edge are dropped, unless needed for connectivity
+    pub max_length_factor: f32,
+}
+
+impl Default for LaneSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            pruning: LanePruning::default(),
+            max_length_factor: 3.0,
+        }
+    }
+}
+
+//the lane graph over Masterik.positions, every star that made it into the triangulation is reachable
+#[derive(Resource, Default)]
+pub struct StarLanes {
+    pub lanes: Vec<(usize, usize)>,
+    //neighbours of every star, indexed like Masterik.positions
+    pub adjacency: Vec<Vec<usize>>,
+    pub median_length: f32,
+}
+
+//marks the entity holding the lane line mesh
+#[derive(Component)]
+pub struct LaneMesh;
+
+struct LaneVertex {
+    position: Point2<f64>,
+    index: usize,
+}
+
+impl HasPosition for LaneVertex {
+    type Scalar = f64;
+
+    fn position(&self) -> Point2<f64> {
+        self.position
+    }
+}
+
+//union find for the connectivity pass
+struct Components {
+    parent: Vec<usize>,
+}
+
+impl Components {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn root(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    //false if both were already connected
+    fn join(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.root(a), self.root(b));
+        self.parent[a] = b;
+        a != b
+    }
+}
+
+fn point(positions: &[(f32, f32, f32)], index: usize) -> Vec2 {
+    let (x, y, _) = positions[index];
+    Vec2::new(x, y)
+}
+
+type LaneVertexHandle<'a> = VertexHandle<'a, LaneVertex>;
+
+//gabriel: no star inside the circle that has the lane as its diameter, for delaunay edges only the
+//two opposite corners of the neighbouring triangles can be inside
+fn is_gabriel(positions: &[(f32, f32, f32)], a: usize, b: usize, opposite: &[usize]) -> bool {
+    let (pa, pb) = (point(positions, a), point(positions, b));
+    opposite.iter().all(|&c| {
+        let pc = point(positions, c);
+        (pa - pc).dot(pb - pc) > 0.0
+    })
+}
+
+//relative neighbourhood: no star closer to both ends than they are to each other, witnesses are
+//looked for among the delaunay neighbours of both ends
+fn is_relative_neighbor(
+    positions: &[(f32, f32, f32)],
+    from: LaneVertexHandle,
+    to: LaneVertexHandle,
+) -> bool {
+    let (a, b) = (from.data().index, to.data().index);
+    let (pa, pb) = (point(positions, a), point(positions, b));
+    let length = pa.distance_squared(pb);
+
+    from.out_edges()
+        .chain(to.out_edges())
+        .map(|edge| edge.to().data().index)
+        .filter(|&c| c != a && c != b)
+        .all(|c| {
+            let pc = point(positions, c);
+            pa.distance_squared(pc).max(pb.distance_squared(pc)) >= length
+        })
+}
+
+//one delaunay edge, with whether each pruning keeps it
+struct LaneEdge {
+    a: usize,
+    b: usize,
+    length: f32,
+    gabriel: bool,
+    relative_neighbor: bool,
+}
+
+impl LaneEdge {
+    fn survives(&self, pruning: LanePruning) -> bool {
+        match pruning {
+            LanePruning::Delaunay => true,
+            LanePruning::Gabriel => self.gabriel,
+            LanePruning::RelativeNeighborhood => self.relative_neighbor,
+        }
+    }
+}
+
+//the triangulated edges of the current galaxy, only rebuilt when the stars change, the lane
+//settings just pick from them
+#[derive(Resource, Default)]
+pub struct LaneGraph {
+    //sorted by length, shortest first
+    edges: Vec<LaneEdge>,
+    //(dropped, kept) for stars on the exact spot of another one, the triangulation only keeps one vertex
+    duplicates: Vec<(usize, usize)>,
+    star_count: usize,
+    pub median_length: f32,
+}
+
+impl LaneGraph {
+    pub fn triangulate(positions: &[(f32, f32, f32)]) -> Result<Self, GalaxyError> {
+        let vertices = positions
+            .iter()
+            .enumerate()
+            .map(|(index, &(x, y, _))| LaneVertex {
+                position: Point2::new(x as f64, y as f64),
+                index,
+            })
+            .collect();
+        let triangulation: DelaunayTriangulation<LaneVertex> =
+            DelaunayTriangulation::bulk_load(vertices).map_err(GalaxyError::Lanes)?;
+
+        let mut edges: Vec<LaneEdge> = triangulation
+            .undirected_edges()
+            .map(|edge| {
+                let [from, to] = edge.vertices();
+                let (a, b) = (from.data().index, to.data().index);
+                let directed = edge.as_directed();
+                let opposite: Vec<usize> =
+                    [directed.opposite_vertex(), directed.rev().opposite_vertex()]
+                        .into_iter()
+                        .flatten()
+                        .map(|vertex| vertex.data().index)
+                        .collect();
+
+                LaneEdge {
+                    a,
+                    b,
+                    length: point(positions, a).distance(point(positions, b)),
+                    gabriel: is_gabriel(positions, a, b, &opposite),
+                    relative_neighbor: is_relative_neighbor(positions, from, to),
+                }
+            })
+            .collect();
+        edges.sort_by(|a, b| a.length.total_cmp(&b.length));
+
+        let mut triangulated = vec![false; positions.len()];
+        for vertex in triangulation.vertices() {
+            triangulated[vertex.data().index] = true;
+        }
+        let duplicates = positions
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| !triangulated[index])
+            .filter_map(|(index, &(x, y, _))| {
+                triangulation
+                    .locate_vertex(Point2::new(x as f64, y as f64))
+                    .map(|kept| (index, kept.data().index))
+            })
+            .collect();
+
+        let median_length = edges.get(edges.len() / 2).map_or(0.0, |edge| edge.length);
+        Ok(Self {
+            edges,
+            duplicates,
+            star_count: positions.len(),
+            median_length,
+        })
+    }
+}
+
+impl StarLanes {
+    //cheap enough to run on every settings change, the triangulation is done once per galaxy
+    pub fn build(graph: &LaneGraph, settings: &LaneSettings) -> Self {
+        if graph.edges.is_empty() {
+            return Self::default();
+        }
+        let max_length = graph.median_length * settings.max_length_factor;
+
+        let mut components = Components::new(graph.star_count);
+        let mut lanes = Vec::new();
+        let mut kept = Vec::with_capacity(graph.edges.len());
+        for edge in graph.edges.iter() {
+            let keep = edge.survives(settings.pruning) && edge.length <= max_length;
+            if keep {
+                components.join(edge.a, edge.b);
+                lanes.push((edge.a, edge.b));
+            }
+            kept.push(keep);
+        }
+
+        //the edges are sorted, so this is kruskal and the fewest and shortest extra lanes reconnect the graph
+        for (edge, kept) in graph.edges.iter().zip(kept) {
+            if !kept && components.join(edge.a, edge.b) {
+                lanes.push((edge.a, edge.b));
+            }
+        }
+
+        //a dropped duplicate gets the lanes of the star it sits on, plus one to that star
+        for &(dropped, kept) in graph.duplicates.iter() {
+            let shared: Vec<usize> = lanes
+                .iter()
+                .filter_map(|&(a, b)| match (a == kept, b == kept) {
+                    (true, _) => Some(b),
+                    (_, true) => Some(a),
+                    _ => None,
+                })
+                .collect();
+            lanes.push((dropped, kept));
+            lanes.extend(shared.into_iter().map(|other| (dropped, other)));
+        }
+
+        let mut adjacency = vec![Vec::new(); graph.star_count];
+        for &(a, b) in lanes.iter() {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+
+        Self {
+            lanes,
+            adjacency,
+            median_length: graph.median_length,
+        }
+    }
+
+    fn mesh(&self, positions: &[(f32, f32, f32)]) -> Mesh {
+        let vertices: Vec<[f32; 3]> = self
+            .lanes
+            .iter()
+            .flat_map(|&(a, b)| {
+                let (pa, pb) = (point(positions, a), point(positions, b));
+                [[pa.x, pa.y, -1.0], [pb.x, pb.y, -1.0]]
+            })
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+        mesh
+    }
+}
+
+//the triangulation has the same triggers as the star grid, the lanes are only picked again when
+//the pruning or the length cut change, showing and hiding them rebuilds nothing
+pub fn rebuild_star_lanes(
+    mut commands: Commands,
+    masterok: Res<Masterik>,
+    settings: Res<LaneSettings>,
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut graph: ResMut<LaneGraph>,
+    mut lanes: ResMut<StarLanes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut toasts: ResMut<ErrorToasts>,
+    old_meshes: Query<Entity, With<LaneMesh>>,
+    mut last_cut: Local<Option<(LanePruning, f32)>>,
+) {
+    let regenerated = ev_generated.read().count() > 0;
+    if masterok.block_input {
+        return;
+    }
+
+    let restarred = regenerated || masterok.positions.len() != graph.star_count;
+    if restarred {
+        *graph = LaneGraph::triangulate(&masterok.positions).unwrap_or_else(|err| {
+            toasts.report(&err);
+            //an empty graph that still matches the stars, so it isn't retried every frame
+            LaneGraph {
+                star_count: masterok.positions.len(),
+                ..default()
+            }
+        });
+    }
+
+    let cut = Some((settings.pruning, settings.max_length_factor));
+    if !restarred && *last_cut == cut {
+        return;
+    }
+    *last_cut = cut;
+
+    for entity in old_meshes.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    *lanes = StarLanes::build(&graph, &settings);
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(lanes.mesh(&masterok.positions)).into(),
+            material: materials.add(ColorMaterial::from(Color::rgba(0.35, 0.55, 0.9, 0.0))),
+            ..default()
+        },
+        LaneMesh,
+    ));
+}
+
+//lanes only show once they are long enough on screen to tell apart
+pub fn fade_star_lanes(
+    settings: Res<LaneSettings>,
+    lanes: Res<StarLanes>,
+    view: Res<State<ViewMode>>,
+    camera_query: Query<&OrthographicProjection, With<GalaxyCamera>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(&Handle<ColorMaterial>, &mut Visibility), With<LaneMesh>>,
+) {
+    let scale = camera_query.get_single().map(|p| p.scale).unwrap_or(1.0);
+    let on_screen = lanes.median_length / scale.max(f32::EPSILON);
+    let fade =
+        ((on_screen - FADE_START_PIXELS) / (FADE_FULL_PIXELS - FADE_START_PIXELS)).clamp(0.0, 1.0);
+    let shown = settings.visible && *view.get() == ViewMode::Galaxy && fade > 0.0;
+
+    for (handle, mut visibility) in query.iter_mut() {
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        //get_mut alone already re-uploads the material, so only touch it when the alpha moved
+        let alpha = fade * LANE_ALPHA;
+        if materials.get(handle).is_some_and(|m| m.color.a() != alpha) {
+            if let Some(material) = materials.get_mut(handle) {
+                material.color.set_a(alpha);
+            }
+        }
+    }
+}
+
+pub fn lane_settings_ui(ui: &mut egui::Ui, settings: &mut LaneSettings, lanes: &StarLanes) {
+    egui::CollapsingHeader::new("Lanes")
+        .id_source("star_lanes")
+        .show(ui, |ui| {
+            ui.checkbox(&mut settings.visible, "Show Lanes");
+            egui::ComboBox::from_label("Pruning")
+                .selected_text(settings.pruning.label())
+                .show_ui(ui, |ui| {
+                    for pruning in LanePruning::ALL {
+                        ui.selectable_value(&mut settings.pruning, pruning, pruning.label());
+                    }
+                });
+            ui.add(
+                egui::Slider::new(&mut settings.max_length_factor, 1.0..=10.0)
+                    .text("Max Length (x median)"),
+            );
+            ui.small(format!("{} lanes", lanes.lanes.len()));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //two jittered 5x5 clusters far apart, only a lane much longer than the rest can join them
+    fn two_clusters() -> Vec<(f32, f32, f32)> {
+        (0..50)
+            .map(|index| {
+                let offset = if index < 25 { 0.0 } else { 100000.0 };
+                let (x, y) = ((index % 25) % 5, (index % 25) / 5);
+                let jitter = ((index * 37) % 11) as f32;
+                (
+                    offset + x as f32 * 100.0 + jitter,
+                    y as f32 * 100.0 - jitter,
+                    500.0,
+                )
+            })
+            .collect()
+    }
+
+    fn reachable(lanes: &StarLanes) -> usize {
+        let mut seen = vec![false; lanes.adjacency.len()];
+        let mut stack = vec![0];
+        seen[0] = true;
+        while let Some(star) = stack.pop() {
+            for &next in &lanes.adjacency[star] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        seen.into_iter().filter(|&seen| seen).count()
+    }
+
+    #[test]
+    fn every_pruning_stays_connected() {
+        let positions = two_clusters();
+        let graph = LaneGraph::triangulate(&positions).unwrap();
+
+        for pruning in LanePruning::ALL {
+            let lanes = StarLanes::build(
+                &graph,
+                &LaneSettings {
+                    pruning,
+                    ..default()
+                },
+            );
+            assert_eq!(reachable(&lanes), positions.len(), "{:?}", pruning);
+
+            //the length cut drops every lane between the clusters and kruskal puts back exactly one
+            let long = lanes
+                .lanes
+                .iter()
+                .filter(|&&(a, b)| point(&positions, a).distance(point(&positions, b)) > 1000.0)
+                .count();
+            assert_eq!(long, 1, "{:?}", pruning);
+        }
+    }
+
+    #[test]
+    fn pruning_only_removes_lanes() {
+        let positions = two_clusters();
+        let graph = LaneGraph::triangulate(&positions).unwrap();
+        let count = |pruning| {
+            StarLanes::build(
+                &graph,
+                &LaneSettings {
+                    pruning,
+                    max_length_factor: f32::MAX,
+                    ..default()
+                },
+            )
+            .lanes
+            .len()
+        };
+        assert!(count(LanePruning::Delaunay) >= count(LanePruning::Gabriel));
+        assert!(count(LanePruning::Gabriel) >= count(LanePruning::RelativeNeighborhood));
+    }
+
+    #[test]
+    fn duplicates_share_the_lanes_of_the_kept_star() {
+        let mut positions = two_clusters();
+        positions.push(positions[7]);
+        positions.push(positions[30]);
+        let graph = LaneGraph::triangulate(&positions).unwrap();
+
+        for pruning in LanePruning::ALL {
+            let lanes = StarLanes::build(
+                &graph,
+                &LaneSettings {
+                    pruning,
+                    ..default()
+                },
+            );
+            assert_eq!(reachable(&lanes), positions.len(), "{:?}", pruning);
+
+            for &(dropped, kept) in graph.duplicates.iter() {
+                assert!(lanes.adjacency[dropped].contains(&kept));
+                for &other in lanes.adjacency[kept]
+                    .iter()
+                    .filter(|&&other| other != dropped)
+                {
+                    assert!(lanes.adjacency[dropped].contains(&other), "{:?}", pruning);
+                }
+            }
+        }
+        assert_eq!(graph.duplicates.len(), 2);
+    }
+
+    #[test]
+    fn empty_galaxy_has_no_lanes() {
+        let graph = LaneGraph::triangulate(&[]).unwrap();
+        assert!(StarLanes::build(&graph, &LaneSettings::default())
+            .lanes
+            .is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::economy::StarEconomy;
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::lanes::StarLanes;
+use crate::naming::proper_name;
+use crate::resources::Masterik;
+use crate::storage::write_export;
+
+//keeps history rolls independent from everything else rolled from the galaxy seed
+const LEGENDS_SALT: u64 = 0x4C45_4745_4E44_5321;
+pub const HISTORY_YEARS: u32 = 3000;
+const YEARS_PER_STEP: u32 = 10;
+const MAX_ALIVE: usize = 10;
+//chance per step that a new civilization rises somewhere
+const FOUNDING_CHANCE: f64 = 0.08;
+//only stars at least this habitable can be a cradle
+const CRADLE_HABITABILITY: f32 = 0.6;
+//chance per border contact that two civilizations at peace go to war
+const WAR_CHANCE: f64 = 0.02;
+const CONQUEST_CHANCE: f64 = 0.25;
+//how many events the panel lists before the selected year
+const RECENT_EVENTS: usize = 8;
+
+const TITLES: [&str; 8] = [
+    "Dominion",
+    "Republic",
+    "Hegemony",
+    "Collective",
+    "Concord",
+    "Empire",
+    "Union",
+    "Theocracy",
+];
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Civilization {
+    pub name: String,
+    pub homeworld: usize,
+    //srgb
+    pub color: [u8; 3],
+    pub founded: u32,
+    pub collapsed: Option<u32>,
+    pub peak_stars: usize,
+}
+
+impl Civilization {
+    pub fn alive_at(&self, year: u32) -> bool {
+        self.founded <= year && self.collapsed.map_or(true, |collapsed| year < collapsed)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type")]
+pub enum LegendEventKind {
+    Founded,
+    Colonized,
+    WarDeclared { against: usize },
+    PeaceMade { with: usize },
+    Conquered { from: usize },
+    Collapsed,
+}
+
+//everything that happened, star is where it happened
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct LegendEvent {
+    pub year: u32,
+    pub civilization: usize,
+    pub star: usize,
+    #[serde(flatten)]
+    pub kind: LegendEventKind,
+}
+
+impl LegendEvent {
+    //colonies are too many to list, everything else makes the timeline
+    pub fn is_notable(&self) -> bool {
+        self.kind != LegendEventKind::Colonized
+    }
+
+    pub fn describe(&self, civilizations: &[Civilization], star_name: &str) -> String {
+        let name = |civ: usize| civilizations.get(civ).map_or("?", |c| c.name.as_str());
+        let actor = name(self.civilization);
+        match self.kind {
+            LegendEventKind::Founded => format!("{} rises at {}", actor, star_name),
+            LegendEventKind::Colonized => format!("{} settles {}", actor, star_name),
+            LegendEventKind::WarDeclared { against } => {
+                format!(
+                    "{} goes to war with {} over {}",
+                    actor,
+                    name(against),
+                    star_name
+                )
+            }
+            LegendEventKind::PeaceMade { with } => {
+                format!("{} makes peace with {}", actor, name(with))
+            }
+            LegendEventKind::Conquered { from } => {
+                format!("{} takes {} from {}", actor, star_name, name(from))
+            }
+            LegendEventKind::Collapsed => format!("{} collapses", actor),
+        }
+    }
+}
+
+//the whole simulated history of one galaxy, events are in order of year
+#[derive(Resource, Default, Serialize)]
+pub struct Legends {
+    pub seed: u64,
+    pub years: u32,
+    pub civilizations: Vec<Civilization>,
+    pub events: Vec<LegendEvent>,
+}
+
+//the year picked on the time slider and who owned what back then
+#[derive(Resource)]
+pub struct LegendsView {
+    pub year: u32,
+    pub owners: Vec<Option<usize>>,
+}
+
+impl Default for LegendsView {
+    fn default() -> Self {
+        Self {
+            year: HISTORY_YEARS,
+            owners: Vec::new(),
+        }
+    }
+}
+
+//asks for the history to be written out as json
+#[derive(Event)]
+pub struct ExportLegends;
+
+#[derive(Resource, Default)]
+pub struct LegendsExport {
+    pub last_path: Option<String>,
+}
+
+//what the panel asked for
+pub enum LegendsAction {
+    Export,
+    FlyTo(usize),
+}
+
+struct War {
+    a: usize,
+    b: usize,
+    until: u32,
+}
+
+//running state of the simulation, the events are the only thing kept afterwards
+struct Simulation<'a> {
+    rng: SmallRng,
+    adjacency: &'a [Vec<usize>],
+    economy: &'a StarEconomy,
+    owners: Vec<Option<usize>>,
+    owned: Vec<Vec<usize>>,
+    wars: Vec<War>,
+    legends: Legends,
+}
+
+impl Simulation<'_> {
+    fn event(&mut self, year: u32, civilization: usize, star: usize, kind: LegendEventKind) {
+        self.legends.events.push(LegendEvent {
+            year,
+            civilization,
+            star,
+            kind,
+        });
+    }
+
+    fn alive(&self) -> Vec<usize> {
+        (0..self.legends.civilizations.len())
+            .filter(|&civ| self.legends.civilizations[civ].collapsed.is_none())
+            .collect()
+    }
+
+    fn at_war(&self, a: usize, b: usize) -> bool {
+        self.wars
+            .iter()
+            .any(|war| (war.a == a && war.b == b) || (war.a == b && war.b == a))
+    }
+
+    fn habitability(&self, star: usize) -> f32 {
+        self.economy.get(star).map_or(0.0, |r| r.habitability)
+    }
+
+    fn claim(&mut self, civ: usize, star: usize) {
+        if let Some(previous) = self.owners[star] {
+            self.owned[previous].retain(|&s| s != star);
+        }
+        self.owners[star] = Some(civ);
+        self.owned[civ].push(star);
+        let civilization = &mut self.legends.civilizations[civ];
+        civilization.peak_stars = civilization.peak_stars.max(self.owned[civ].len());
+    }
+
+    fn found(&mut self, year: u32) {
+        if self.alive().len() >= MAX_ALIVE || !self.rng.gen_bool(FOUNDING_CHANCE) {
+            return;
+        }
+        let mut cradle = None;
+        for _ in 0..20 {
+            let star = self.rng.gen_range(0..self.owners.len());
+            if self.owners[star].is_none() && self.habitability(star) >= CRADLE_HABITABILITY {
+                cradle = Some(star);
+                break;
+            }
+        }
+        let Some(homeworld) = cradle else {
+            return;
+        };
+
+        let civ = self.legends.civilizations.len();
+        let title = TITLES[self.rng.gen_range(0..TITLES.len())];
+        //golden angle hues, civilizations founded one after another rarely look alike
+        let [r, g, b, _] = Color::hsl((civ as f32 * 137.5) % 360.0, 0.75, 0.55).as_rgba_u8();
+        self.legends.civilizations.push(Civilization {
+            name: format!(
+                "{} {}",
+                proper_name(self.legends.seed ^ LEGENDS_SALT, homeworld),
+                title
+            ),
+            homeworld,
+            color: [r, g, b],
+            founded: year,
+            collapsed: None,
+            peak_stars: 0,
+        });
+        self.owned.push(Vec::new());
+        self.claim(civ, homeworld);
+        self.event(year, civ, homeworld, LegendEventKind::Founded);
+    }
+
+    fn collapse(&mut self, year: u32, civ: usize) {
+        for star in std::mem::take(&mut self.owned[civ]) {
+            self.owners[star] = None;
+        }
+        self.wars.retain(|war| war.a != civ && war.b != civ);
+        self.legends.civilizations[civ].collapsed = Some(year);
+        let homeworld = self.legends.civilizations[civ].homeworld;
+        self.event(year, civ, homeworld, LegendEventKind::Collapsed);
+    }
+
+    //bigger empires reach out more often, each attempt goes one lane out from a random owned star
+    fn expand(&mut self, year: u32, civ: usize) {
+        let attempts = (1 + self.owned[civ].len() / 50).min(6);
+        for _ in 0..attempts {
+            let Some(&from) = self.owned[civ].choose(&mut self.rng) else {
+                return;
+            };
+            let Some(&to) = self.adjacency[from].choose(&mut self.rng) else {
+                continue;
+            };
+
+            match self.owners[to] {
+                None => {
+                    if self
+                        .rng
+                        .gen_bool((0.3 + self.habitability(to) as f64 * 0.5).min(1.0))
+                    {
+                        self.claim(civ, to);
+                        self.event(year, civ, to, LegendEventKind::Colonized);
+                    }
+                }
+                Some(other) if other == civ => (),
+                Some(other) if self.at_war(civ, other) => {
+                    if self.rng.gen_bool(CONQUEST_CHANCE) {
+                        self.claim(civ, to);
+                        self.event(year, civ, to, LegendEventKind::Conquered { from: other });
+                        if self.owned[other].is_empty() {
+                            self.collapse(year, other);
+                        }
+                    }
+                }
+                Some(other) => {
+                    if self.rng.gen_bool(WAR_CHANCE) {
+                        let until = year + self.rng.gen_range(50..300);
+                        self.wars.push(War {
+                            a: civ,
+                            b: other,
+                            until,
+                        });
+                        self.event(
+                            year,
+                            civ,
+                            to,
+                            LegendEventKind::WarDeclared { against: other },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn make_peace(&mut self, year: u32) {
+        let (ended, ongoing): (Vec<War>, Vec<War>) = std::mem::take(&mut self.wars)
+            .into_iter()
+            .partition(|war| war.until <= year);
+        self.wars = ongoing;
+        for war in ended {
+            let homeworld = self.legends.civilizations[war.a].homeworld;
+            self.event(
+                year,
+                war.a,
+                homeworld,
+                LegendEventKind::PeaceMade { with: war.b },
+            );
+        }
+    }
+
+    //old empires grow brittle, every war they fight makes it worse
+    fn decline(&mut self, year: u32) {
+        for civ in self.alive() {
+            let age = (year - self.legends.civilizations[civ].founded) as f64;
+            let wars = self
+                .wars
+                .iter()
+                .filter(|w| w.a == civ || w.b == civ)
+                .count() as f64;
+            let chance = (age / 1000.0).powi(2) * 0.01 + wars * 0.002;
+            if self.rng.gen_bool(chance.min(1.0)) {
+                self.collapse(year, civ);
+            }
+        }
+    }
+}
+
+impl Legends {
+    //the same galaxy always has the same history
+    pub fn simulate(seed: u64, lanes: &StarLanes, economy: &StarEconomy) -> Self {
+        let len = economy.stars.len();
+        if len == 0 || lanes.adjacency.len() != len {
+            return Self::default();
+        }
+
+        let mut sim = Simulation {
+            rng: SmallRng::seed_from_u64(seed ^ LEGENDS_SALT),
+            adjacency: &lanes.adjacency,
+            economy,
+            owners: vec![None; len],
+            owned: Vec::new(),
+            wars: Vec::new(),
+            legends: Legends {
+                seed,
+                years: HISTORY_YEARS,
+                ..default()
+            },
+        };
+
+        for year in (0..=HISTORY_YEARS).step_by(YEARS_PER_STEP as usize) {
+            sim.found(year);
+            for civ in sim.alive() {
+                //a civilization can be conquered away by one that expanded before it this step
+                if sim.legends.civilizations[civ].collapsed.is_none() {
+                    sim.expand(year, civ);
+                }
+            }
+            sim.make_peace(year);
+            sim.decline(year);
+        }
+
+        sim.legends
+    }
+
+    //replays the events up to the end of the given year
+    pub fn owners_at(&self, year: u32, len: usize) -> Vec<Option<usize>> {
+        let mut owners = vec![None; len];
+        for event in self.events.iter().take_while(|event| event.year <= year) {
+            match event.kind {
+                LegendEventKind::Founded
+                | LegendEventKind::Colonized
+                | LegendEventKind::Conquered { .. } => {
+                    if let Some(owner) = owners.get_mut(event.star) {
+                        *owner = Some(event.civilization);
+                    }
+                }
+                LegendEventKind::Collapsed => {
+                    for owner in owners.iter_mut() {
+                        if *owner == Some(event.civilization) {
+                            *owner = None;
+                        }
+                    }
+                }
+                LegendEventKind::WarDeclared { .. } | LegendEventKind::PeaceMade { .. } => (),
+            }
+        }
+        owners
+    }
+
+    pub fn export(&self) -> Result<String, GalaxyError> {
+        let text =
+            serde_json::to_string_pretty(self).map_err(|err| GalaxyError::Storage(err.into()))?;
+        let file_name = format!("legends-{}.json", self.seed);
+        write_export(&file_name, &text)
+            .map(|path| path.display().to_string())
+            .map_err(GalaxyError::Storage)
+    }
+}
+
+//history needs both the lanes to spread along and the habitability to start from
+pub fn rebuild_legends(
+    masterok: Res<Masterik>,
+    lanes: Res<StarLanes>,
+    economy: Res<StarEconomy>,
+    mut legends: ResMut<Legends>,
+) {
+    if masterok.block_input || !(lanes.is_changed() || economy.is_changed()) {
+        return;
+    }
+    *legends = Legends::simulate(masterok.gen_seed, &lanes, &economy);
+}
+
+pub fn update_legends_view(
+    masterok: Res<Masterik>,
+    legends: Res<Legends>,
+    mut view: ResMut<LegendsView>,
+) {
+    if !(legends.is_changed() || view.is_changed()) {
+        return;
+    }
+    let year = view.year;
+    view.owners = legends.owners_at(year, masterok.positions.len());
+}
+
+pub fn export_legends_system(
+    mut ev_export: EventReader<ExportLegends>,
+    masterok: Res<Masterik>,
+    legends: Res<Legends>,
+    mut export: ResMut<LegendsExport>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    if ev_export.read().count() == 0 || masterok.block_input {
+        return;
+    }
+
+    match legends.export() {
+        Ok(path) => {
+            info!("legends written to {}", path);
+            export.last_path = Some(path);
+        }
+        Err(err) => toasts.report(&err),
+    }
+}
+
+pub fn legends_ui(
+    ui: &mut egui::Ui,
+    legends: &Legends,
+    year: &mut u32,
+    export: &LegendsExport,
+    star_name: impl Fn(usize) -> String,
+) -> Option<LegendsAction> {
+    let mut action = None;
+    egui::CollapsingHeader::new("Legends")
+        .id_source("legends")
+        .show(ui, |ui| {
+            if legends.civilizations.is_empty() {
+                ui.label("No civilizations rose in this galaxy");
+                return;
+            }
+
+            ui.add(egui::Slider::new(year, 0..=legends.years).text("Year"));
+
+            ui.horizontal_wrapped(|ui| {
+                for civ in legends.civilizations.iter().filter(|c| c.alive_at(*year)) {
+                    let [r, g, b] = civ.color;
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), &civ.name);
+                }
+            });
+
+            let recent: Vec<&LegendEvent> = legends
+                .events
+                .iter()
+                .take_while(|event| event.year <= *year)
+                .filter(|event| event.is_notable())
+                .collect();
+            for event in recent.iter().rev().take(RECENT_EVENTS) {
+                let text = format!(
+                    "{}: {}",
+                    event.year,
+                    event.describe(&legends.civilizations, &star_name(event.star))
+                );
+                if ui.small_button(text).clicked() {
+                    action = Some(LegendsAction::FlyTo(event.star));
+                }
+            }
+
+            if ui.button("Export Legends").clicked() {
+                action = Some(LegendsAction::Export);
+            }
+            if let Some(path) = &export.last_path {
+                ui.small(format!("Saved to {}", path));
+            }
+        });
+    action
+}
@@ -0,0 +1,50 @@
+//! Procedural spiral galaxy viewer for Bevy.
+//!
+//! Add [`GalaxyPlugin`] for star generation and the camera, [`GalaxyInputPlugin`] for
+//! keyboard/mouse/touch/gamepad controls and [`GalaxyUiPlugin`] for the side panel.
+//! Lanes, routes, factions, the economy, overlays, planets, the system view, persistence,
+//! exploration, bookmarks, legends, camera paths and attract mode come as plugins of their own.
+
+pub mod attract;
+pub mod bookmarks;
+pub mod camera;
+pub mod catalog;
+pub mod cinematic;
+pub mod controls;
+pub mod economy;
+pub mod error;
+pub mod exploration;
+pub mod factions;
+pub mod galaxy;
+pub mod history;
+pub mod input;
+pub mod lanes;
+pub mod legends;
+pub mod naming;
+pub mod overlay;
+pub mod planets;
+pub mod plugin;
+pub mod preferences;
+pub mod quit;
+pub mod resources;
+pub mod routes;
+pub mod savegame;
+pub mod search;
+pub mod selection;
+pub mod settings_panel;
+pub mod share;
+pub mod spatial;
+pub mod storage;
+pub mod system_view;
+pub mod ui;
+
+pub use camera::GalaxyCamera;
+pub use error::GalaxyError;
+pub use plugin::{
+    CameraOwnership, GalaxyAttractPlugin, GalaxyBookmarksPlugin, GalaxyCinematicPlugin,
+    GalaxyEconomyPlugin, GalaxyExplorationPlugin, GalaxyFactionsPlugin, GalaxyInputPlugin,
+    GalaxyLanesPlugin, GalaxyLegendsPlugin, GalaxyOverlayPlugin, GalaxyPersistencePlugin,
+    GalaxyPlanetsPlugin, GalaxyPlugin, GalaxyRoutesPlugin, GalaxySet, GalaxySystemViewPlugin,
+    GalaxyUiPlugin,
+};
+pub use resources::{GalaxyGenerated, GalaxySettings, StarData, StarSelected};
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::resources::StarClass;
+
+//sectors are wedges around the core lettered counter clockwise from the +x axis, and rings of this width
+const SECTOR_WEDGES: usize = 12;
+const SECTOR_RING_WIDTH: f32 = 250000.0;
+
+const ONSETS: [&str; 20] = [
+    "b", "c", "d", "f", "g", "k", "l", "m", "n", "p", "r", "s", "t", "v", "z", "th", "sh", "kr",
+    "dr", "",
+];
+const VOWELS: [&str; 10] = ["a", "e", "i", "o", "u", "ae", "ia", "or", "ei", "y"];
+const CODAS: [&str; 8] = ["", "", "", "n", "r", "s", "x", "l"];
+
+//splitmix64, good enough to scatter (seed, index) pairs into unrelated numbers
+fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+//per star seed that everything derived from a star (name, planets) starts from
+pub fn star_seed(seed: u64, index: usize) -> u64 {
+    mix(seed ^ mix(index as u64))
+}
+
+fn pick<'a>(list: &[&'a str], roll: &mut u64) -> &'a str {
+    *roll = mix(*roll);
+    list[(*roll % list.len() as u64) as usize]
+}
+
+//pronounceable two or three syllable name, the same seed and index always give the same name
+pub fn proper_name(seed: u64, index: usize) -> String {
+    let mut roll = star_seed(seed, index);
+    let syllables = 2 + (roll % 2) as usize;
+
+    let mut name = String::new();
+    for syllable in 0..syllables {
+        name.push_str(pick(&ONSETS, &mut roll));
+        name.push_str(pick(&VOWELS, &mut roll));
+        if syllable + 1 == syllables {
+            name.push_str(pick(&CODAS, &mut roll));
+        }
+    }
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+//sector the position falls into, like "C4" for the third wedge and fourth ring
+pub fn sector(position: Vec2) -> String {
+    let angle = position
+        .y
+        .atan2(position.x)
+        .rem_euclid(std::f32::consts::TAU);
+    let wedge =
+        ((angle / std::f32::consts::TAU * SECTOR_WEDGES as f32) as usize).min(SECTOR_WEDGES - 1);
+    let ring = (position.length() / SECTOR_RING_WIDTH) as usize;
+    format!("{}{}", (b'A' + wedge as u8) as char, ring + 1)
+}
+
+//catalog style designation, the index keeps it unique within a galaxy
+pub fn designation(index: usize, position: Vec2) -> String {
+    format!("KSC {}-{:06}", sector(position), index)
+}
+
+//bright stars get proper names, everything else only a designation
+pub fn star_name(seed: u64, index: usize, class: StarClass, position: Vec2) -> String {
+    match class {
+        StarClass::O | StarClass::B | StarClass::A => proper_name(seed, index),
+        _ => designation(index, position),
+    }
+}
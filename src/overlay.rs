@@ -0,0 +1,276 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::economy::{ResourceKind, StarEconomy};
+use crate::factions::Territories;
+use crate::lanes::StarLanes;
+use crate::legends::{Legends, LegendsView};
+use crate::resources::Masterik;
+use crate::spatial::StarGrid;
+use crate::system_view::ViewMode;
+
+//resolution of the longer side of the overlay texture
+const OVERLAY_SIZE: u32 = 512;
+//pixels further than this many pixel widths from any star stay transparent
+const REACH_PIXELS: f32 = 6.0;
+const FILL_ALPHA: u8 = 60;
+const BORDER_ALPHA: u8 = 200;
+//heatmaps go from this alpha at zero to HEAT_MAX_ALPHA at one
+const HEAT_MIN_ALPHA: f32 = 30.0;
+const HEAT_MAX_ALPHA: f32 = 170.0;
+
+//what gets painted over the galaxy
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverlayMode {
+    #[default]
+    Off,
+    Territories,
+    Heatmap(ResourceKind),
+    //territories as they were at the year on the legends time slider
+    History,
+}
+
+impl OverlayMode {
+    pub const ALL: [OverlayMode; 7] = [
+        OverlayMode::Off,
+        OverlayMode::Territories,
+        OverlayMode::Heatmap(ResourceKind::Metals),
+        OverlayMode::Heatmap(ResourceKind::Gas),
+        OverlayMode::Heatmap(ResourceKind::Exotic),
+        OverlayMode::Heatmap(ResourceKind::Habitability),
+        OverlayMode::History,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OverlayMode::Off => "Off",
+            OverlayMode::Territories => "Territories",
+            OverlayMode::Heatmap(kind) => kind.label(),
+            OverlayMode::History => "History",
+        }
+    }
+}
+
+//marks the sprite showing the overlay texture
+#[derive(Component)]
+pub struct OverlaySprite;
+
+//area the overlay covers in world units
+struct OverlayBounds {
+    min: Vec2,
+    size: Vec2,
+    width: u32,
+    height: u32,
+}
+
+impl OverlayBounds {
+    fn new(positions: &[(f32, f32, f32)]) -> Option<Self> {
+        let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+        for &(x, y, _) in positions {
+            min = min.min(Vec2::new(x, y));
+            max = max.max(Vec2::new(x, y));
+        }
+        let size = max - min;
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return None;
+        }
+
+        let longest = size.x.max(size.y);
+        Some(Self {
+            min,
+            size,
+            width: ((size.x / longest * OVERLAY_SIZE as f32) as u32).max(1),
+            height: ((size.y / longest * OVERLAY_SIZE as f32) as u32).max(1),
+        })
+    }
+
+    //world position of the center of a pixel, row 0 is the top of the image
+    fn world(&self, x: u32, y: u32) -> Vec2 {
+        Vec2::new(
+            self.min.x + (x as f32 + 0.5) / self.width as f32 * self.size.x,
+            self.min.y + self.size.y - (y as f32 + 0.5) / self.height as f32 * self.size.y,
+        )
+    }
+}
+
+//cold blue through yellow to hot red, more opaque the higher the value
+fn heat_color(value: f32) -> [u8; 4] {
+    let value = value.clamp(0.0, 1.0);
+    let [r, g, b, _] = Color::hsl((1.0 - value) * 240.0, 0.9, 0.5).as_rgba_u8();
+    let alpha = HEAT_MIN_ALPHA + (HEAT_MAX_ALPHA - HEAT_MIN_ALPHA) * value;
+    [r, g, b, alpha as u8]
+}
+
+//paints every pixel with the color of its nearest star, with borders on the pixels where the
+//color changes are drawn more opaque
+fn paint<F>(
+    positions: &[(f32, f32, f32)],
+    grid: &StarGrid,
+    borders: bool,
+    color: F,
+) -> Option<(Image, Vec2, Vec2)>
+where
+    F: Fn(usize) -> Option<[u8; 4]>,
+{
+    let bounds = OverlayBounds::new(positions)?;
+    let reach = bounds.size.x / bounds.width as f32 * REACH_PIXELS;
+
+    let cells: Vec<Option<[u8; 4]>> = (0..bounds.height)
+        .flat_map(|y| (0..bounds.width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            grid.nearest(positions, bounds.world(x, y), reach)
+                .and_then(|(star, _)| color(star))
+        })
+        .collect();
+
+    let at = |x: u32, y: u32| cells[(y * bounds.width + x) as usize];
+    let mut data = Vec::with_capacity(cells.len() * 4);
+    for y in 0..bounds.height {
+        for x in 0..bounds.width {
+            let here = at(x, y);
+            let border = borders
+                && [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ]
+                .into_iter()
+                .filter(|&(nx, ny)| nx < bounds.width && ny < bounds.height)
+                .any(|(nx, ny)| at(nx, ny) != here);
+
+            match here {
+                Some([r, g, b, _]) if border => data.extend([r, g, b, BORDER_ALPHA]),
+                Some(pixel) => data.extend(pixel),
+                None => data.extend([0, 0, 0, 0]),
+            }
+        }
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width: bounds.width,
+            height: bounds.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    Some((image, bounds.min + bounds.size / 2.0, bounds.size))
+}
+
+//repaints whenever the mode or the data behind it changes
+pub fn refresh_overlay(
+    mut commands: Commands,
+    mode: Res<OverlayMode>,
+    masterok: Res<Masterik>,
+    grid: Res<StarGrid>,
+    territories: Option<Res<Territories>>,
+    economy: Option<Res<StarEconomy>>,
+    lanes: Option<Res<StarLanes>>,
+    legends: Option<Res<Legends>>,
+    legends_view: Option<Res<LegendsView>>,
+    mut images: ResMut<Assets<Image>>,
+    old_sprites: Query<Entity, With<OverlaySprite>>,
+) {
+    //a repaint is a few hundred thousand grid lookups, so only for changes the mode actually shows
+    let changed = mode.is_changed()
+        || grid.is_changed()
+        || match *mode {
+            OverlayMode::Off => false,
+            OverlayMode::Territories => territories
+                .as_ref()
+                .is_some_and(|territories| territories.is_changed()),
+            OverlayMode::Heatmap(_) => {
+                economy.as_ref().is_some_and(|economy| economy.is_changed())
+                    || lanes.as_ref().is_some_and(|lanes| lanes.is_changed())
+            }
+            OverlayMode::History => legends_view.as_ref().is_some_and(|view| view.is_changed()),
+        };
+    if masterok.block_input || !changed {
+        return;
+    }
+
+    for entity in old_sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let painted = match *mode {
+        OverlayMode::Off => None,
+        //each of these paints nothing without the plugin behind its data
+        OverlayMode::Territories => territories.and_then(|territories| {
+            paint(&masterok.positions, &grid, true, |star| {
+                let [r, g, b] = territories.owner(star)?.color;
+                Some([r, g, b, FILL_ALPHA])
+            })
+        }),
+        //averaged with the lane neighbours, single stars are too noisy to read a trend from
+        OverlayMode::Heatmap(kind) => economy.and_then(|economy| {
+            paint(&masterok.positions, &grid, false, |star| {
+                let neighbors = lanes
+                    .as_ref()
+                    .and_then(|lanes| lanes.adjacency.get(star))
+                    .map_or(&[][..], |n| n.as_slice());
+                let values: Vec<f32> = std::iter::once(&star)
+                    .chain(neighbors)
+                    .filter_map(|&index| economy.get(index))
+                    .map(|resources| resources.get(kind))
+                    .collect();
+                (!values.is_empty())
+                    .then(|| heat_color(values.iter().sum::<f32>() / values.len() as f32))
+            })
+        }),
+        OverlayMode::History => legends.zip(legends_view).and_then(|(legends, view)| {
+            paint(&masterok.positions, &grid, true, |star| {
+                let civ = view.owners.get(star).copied().flatten()?;
+                let [r, g, b] = legends.civilizations.get(civ)?.color;
+                Some([r, g, b, FILL_ALPHA])
+            })
+        }),
+    };
+    let Some((image, center, size)) = painted else {
+        return;
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture: images.add(image),
+            sprite: Sprite {
+                custom_size: Some(size),
+                ..default()
+            },
+            //under the lanes and the stars
+            transform: Transform::from_translation(center.extend(-2.0)),
+            ..default()
+        },
+        OverlaySprite,
+    ));
+}
+
+pub fn overlay_visibility(
+    view: Res<State<ViewMode>>,
+    mut query: Query<&mut Visibility, With<OverlaySprite>>,
+) {
+    for mut visibility in query.iter_mut() {
+        visibility.set_if_neq(if *view.get() == ViewMode::Galaxy {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+pub fn overlay_mode_ui(ui: &mut egui::Ui, mode: &mut OverlayMode) {
+    egui::ComboBox::from_label("Overlay")
+        .selected_text(mode.label())
+        .show_ui(ui, |ui| {
+            for option in OverlayMode::ALL {
+                ui.selectable_value(mode, option, option.label());
+            }
+        });
+}
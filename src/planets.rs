@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::naming::star_seed;
+use crate::resources::{GalaxyGenerated, Masterik, StarClass, StarData, StarSelected};
+
+//keeps planet rolls independent from the name rolls of the same star
+const PLANET_SALT: u64 = 0x504C_414E_4554_5321;
+//chance that an orbit slot holds an asteroid belt instead of a planet
+const BELT_CHANCE: f64 = 0.12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlanetKind {
+    Rocky,
+    GasGiant,
+    Ice,
+}
+
+impl PlanetKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlanetKind::Rocky => "Rocky",
+            PlanetKind::GasGiant => "Gas Giant",
+            PlanetKind::Ice => "Ice",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Planet {
+    //distance from the star in AU
+    pub orbit: f32,
+    pub kind: PlanetKind,
+    //in earth radii
+    pub radius: f32,
+    pub moons: u32,
+    pub habitable: bool,
+    //orbital period in earth years
+    pub period: f32,
+    //starting angle on the orbit in radians
+    pub phase: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AsteroidBelt {
+    pub orbit: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StarSystem {
+    pub star: usize,
+    pub class: StarClass,
+    //inner and outer edge in AU
+    pub habitable_zone: (f32, f32),
+    pub planets: Vec<Planet>,
+    pub belts: Vec<AsteroidBelt>,
+}
+
+//luminosity and mass relative to the sun, and how many orbit slots a class gets
+fn class_traits(class: StarClass) -> (f32, f32, std::ops::Range<usize>) {
+    match class {
+        StarClass::O => (30000.0, 40.0, 0..3),
+        StarClass::B => (1000.0, 8.0, 0..5),
+        StarClass::A => (20.0, 2.0, 1..7),
+        StarClass::F => (3.0, 1.3, 1..10),
+        StarClass::G => (1.0, 1.0, 1..10),
+        StarClass::K => (0.4, 0.7, 1..9),
+        StarClass::M => (0.04, 0.3, 0..7),
+    }
+}
+
+impl StarSystem {
+    //the same galaxy seed and star index always give the same system
+    pub fn generate(seed: u64, star: usize, class: StarClass) -> Self {
+        let mut rng = SmallRng::seed_from_u64(star_seed(seed, star) ^ PLANET_SALT);
+        let (luminosity, mass, slots) = class_traits(class);
+
+        let light = luminosity.sqrt();
+        let habitable_zone = (light * 0.95, light * 1.37);
+        //past this water freezes and gas giants and ice worlds form
+        let frost_line = light * 4.85;
+
+        let mut planets = Vec::new();
+        let mut belts = Vec::new();
+        let mut orbit = (light * 0.2).max(0.03) * rng.gen_range(0.7..1.3);
+
+        for _ in 0..rng.gen_range(slots) {
+            if rng.gen_bool(BELT_CHANCE) {
+                belts.push(AsteroidBelt { orbit });
+            } else {
+                let kind = if orbit < frost_line {
+                    //the odd hot jupiter that migrated inwards
+                    if rng.gen_bool(0.08) {
+                        PlanetKind::GasGiant
+                    } else {
+                        PlanetKind::Rocky
+                    }
+                } else if orbit < frost_line * 8.0 && rng.gen_bool(0.6) {
+                    PlanetKind::GasGiant
+                } else {
+                    PlanetKind::Ice
+                };
+
+                let (radius, moons) = match kind {
+                    PlanetKind::Rocky => (rng.gen_range(0.3..2.2), rng.gen_range(0..3)),
+                    PlanetKind::GasGiant => (rng.gen_range(3.5..14.0), rng.gen_range(2..40)),
+                    PlanetKind::Ice => (rng.gen_range(0.8..4.5), rng.gen_range(0..6)),
+                };
+
+                planets.push(Planet {
+                    orbit,
+                    kind,
+                    radius,
+                    moons,
+                    habitable: kind == PlanetKind::Rocky
+                        && orbit >= habitable_zone.0
+                        && orbit <= habitable_zone.1,
+                    //kepler's third law with the orbit in AU and the mass in suns
+                    period: (orbit.powi(3) / mass).sqrt(),
+                    phase: rng.gen_range(0.0..std::f32::consts::TAU),
+                });
+            }
+
+            //roughly titius-bode spacing
+            orbit *= rng.gen_range(1.4..2.1);
+        }
+
+        Self {
+            star,
+            class,
+            habitable_zone,
+            planets,
+            belts,
+        }
+    }
+}
+
+//systems of stars that have been looked at, generated the first time a star is selected
+#[derive(Resource, Default)]
+pub struct SystemCache {
+    pub systems: HashMap<usize, StarSystem>,
+}
+
+impl SystemCache {
+    //adding and removing stars can put a different star under an index, so the class has to match too
+    pub fn get(&self, star: usize, class: StarClass) -> Option<&StarSystem> {
+        self.systems
+            .get(&star)
+            .filter(|system| system.class == class)
+    }
+
+    pub fn get_or_generate(&mut self, seed: u64, star: usize, class: StarClass) -> &StarSystem {
+        if self.get(star, class).is_none() {
+            self.systems
+                .insert(star, StarSystem::generate(seed, star, class));
+        }
+        &self.systems[&star]
+    }
+
+    //cached systems sorted by star, the order exports use
+    pub fn sorted(&self) -> Vec<&StarSystem> {
+        let mut systems: Vec<&StarSystem> = self.systems.values().collect();
+        systems.sort_by_key(|system| system.star);
+        systems
+    }
+}
+
+pub fn generate_selected_system(
+    mut ev_selected: EventReader<StarSelected>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    mut cache: ResMut<SystemCache>,
+) {
+    for &StarSelected(star) in ev_selected.read() {
+        let Some(star) = star else {
+            continue;
+        };
+        let Some(&(_, _, radius)) = masterok.positions.get(star) else {
+            continue;
+        };
+        let class = StarClass::from_radius(radius, &star_data);
+        cache.get_or_generate(masterok.gen_seed, star, class);
+    }
+}
+
+//a new galaxy has different stars behind every index
+pub fn clear_system_cache(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut cache: ResMut<SystemCache>,
+) {
+    if ev_generated.read().count() > 0 {
+        cache.systems.clear();
+    }
+}
+
+pub fn star_system_ui(ui: &mut egui::Ui, system: &StarSystem) {
+    egui::CollapsingHeader::new(format!(
+        "System ({} planets, {} belts)",
+        system.planets.len(),
+        system.belts.len()
+    ))
+    .id_source("star_system")
+    .show(ui, |ui| {
+        ui.small(format!(
+            "Habitable zone {:.2} - {:.2} AU",
+            system.habitable_zone.0, system.habitable_zone.1
+        ));
+
+        egui::Grid::new("star_system_grid")
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.label("AU");
+                ui.label("Type");
+                ui.label("Size");
+                ui.label("Moons");
+                ui.end_row();
+
+                for planet in &system.planets {
+                    ui.label(format!("{:.2}", planet.orbit));
+                    if planet.habitable {
+                        ui.colored_label(egui::Color32::LIGHT_GREEN, planet.kind.label());
+                    } else {
+                        ui.label(planet.kind.label());
+                    }
+                    ui.label(format!("{:.1}", planet.radius));
+                    ui.label(planet.moons.to_string());
+                    ui.end_row();
+                }
+
+                for belt in &system.belts {
+                    ui.label(format!("{:.2}", belt.orbit));
+                    ui.label("Asteroid Belt");
+                    ui.end_row();
+                }
+            });
+    });
+}
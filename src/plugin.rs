@@ -0,0 +1,628 @@
+use bevy::asset::embedded_asset;
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy_egui::{EguiPlugin, EguiSet};
+use egui_ratatui::RataguiBackend;
+
+use crate::attract::{
+    attract_fade_system, detect_user_input, restore_attract_layers, run_attract_mode, AttractMode,
+    AttractSettings,
+};
+use crate::bookmarks::{
+    add_bookmark_system, bookmark_keys_system, jump_to_bookmark_system,
+    load_bookmarks_on_regenerate, save_bookmarks, AddBookmark, BookmarkDraft, Bookmarks,
+    IncomingBookmarks, JumpToBookmark,
+};
+use crate::camera::{
+    attach_camera_controller, mouse_camera_system, smooth_camera_system, touch_camera_system,
+    update_camera_limits, CameraLimits, PointerDrag,
+};
+use crate::catalog::{export_catalog_system, CatalogExport, ExportCatalog};
+use crate::cinematic::{
+    camera_path_keys_system, play_camera_path, record_camera_path, AddKeyframe, CameraPath,
+    Cinematic,
+};
+use crate::controls::keyboard_input_system;
+use crate::economy::{rebuild_star_economy, StarEconomy};
+use crate::error::{error_toast_system, ErrorToasts};
+use crate::exploration::{
+    apply_fog_of_war, draw_sensor_range, load_exploration_on_regenerate, save_exploration,
+    update_explored_stars, Exploration, ExploredStars,
+};
+use crate::factions::{
+    clear_capitals_on_regenerate, rebuild_territories, FactionSettings, Territories,
+};
+use crate::galaxy::{
+    despawn_all_stars, request_initial_stars, spawn_galaxy_camera, spawn_initial_stars, star_adder,
+    star_remover, star_watcher,
+};
+use crate::history::{
+    history_input_system, record_settings_history, replay_history_steps, SettingsHistory,
+};
+use crate::input::{load_key_map, update_action_state, ActionState, GamepadMap, KeyMap};
+use crate::lanes::{fade_star_lanes, rebuild_star_lanes, LaneGraph, LaneSettings, StarLanes};
+use crate::legends::{
+    export_legends_system, rebuild_legends, update_legends_view, ExportLegends, Legends,
+    LegendsExport, LegendsView,
+};
+use crate::overlay::{overlay_visibility, refresh_overlay, OverlayMode};
+use crate::planets::{clear_system_cache, generate_selected_system, SystemCache};
+use crate::preferences::{
+    apply_bloom_preference, load_preferences, restore_last_galaxy, save_preferences,
+    sync_preferences, Preferences,
+};
+use crate::quit::{finish_quit, quit_dialog_system, quit_request_system, AppQuitting, QuitDialog};
+use crate::resources::{
+    BevyTerminal, ChangeSeed, GalaxyGenerated, GalaxySettings, Masterik, RespawnStars, SpawnStars,
+    StarData, StarSelected, StarsAdded, StarsRemoved, UiFocus,
+};
+use crate::routes::{
+    clear_route_on_regenerate, draw_route, plan_route_system, PlannedRoute, RouteRequest,
+};
+use crate::savegame::{
+    finish_pending_load, load_from_slot_system, save_to_slot_system, scan_save_slots, LoadFromSlot,
+    PendingLoad, SaveSlots, SaveToSlot,
+};
+use crate::search::{fly_to_star_system, FlyToStar, StarSearch};
+use crate::selection::{
+    clear_selection_on_regenerate, highlight_selected_star, pick_star_system, SelectedStar,
+};
+use crate::settings_panel::{sync_settings_draft, SettingsDraft};
+use crate::share::{open_share_code_system, OpenShareCode, ShareCodes};
+use crate::spatial::{rebuild_star_grid, update_hover_info, HoverInfo, StarGrid};
+use crate::system_view::{
+    animate_orbits, draw_system_orbits, enter_system_view, exit_system_view,
+    leave_system_view_on_change, system_view_input, ViewMode,
+};
+use crate::ui::{
+    attract_section, bookmarks_section, camera_path_section, catalog_section, display_section,
+    draw_info_terminal, exploration_section, factions_section, history_section, lanes_section,
+    legends_section, overlay_section, route_section, saves_section, search_section,
+    selected_star_section, settings_section, share_section, side_panel_system, system_view_section,
+    toggle_panel_system, update_ui_focus, AddPanelSection, PanelSlot, PanelState,
+};
+
+//ordering points between plugins, so none of them has to name systems of another
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GalaxySet {
+    //reads the controls, runs before the camera is smoothed
+    Input,
+    //resets or reloads per galaxy state once a new galaxy exists, loaded saves are put on top afterwards
+    Regenerated,
+}
+
+//marks an app that picked its own galaxy, the remembered one is not restored then
+#[derive(Resource)]
+struct FixedGalaxy;
+
+//who provides the camera the galaxy is looked at through
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraOwnership {
+    //the plugin spawns an hdr camera with bloom
+    #[default]
+    Spawn,
+    //the app spawns its own camera and tags it with GalaxyCamera
+    External,
+}
+
+//star generation, camera smoothing and the galaxy events, nothing here touches the disk, everything else is optional
+#[derive(Clone, Default)]
+pub struct GalaxyPlugin {
+    pub settings: Option<GalaxySettings>,
+    pub star_data: StarData,
+    pub camera: CameraOwnership,
+}
+
+impl GalaxyPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //start from a fixed galaxy instead of a time based seed
+    pub fn with_settings(mut self, settings: GalaxySettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    pub fn with_star_data(mut self, star_data: StarData) -> Self {
+        self.star_data = star_data;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: CameraOwnership) -> Self {
+        self.camera = camera;
+        self
+    }
+}
+
+impl Plugin for GalaxyPlugin {
+    fn build(&self, app: &mut App) {
+        let mut masterok = Masterik::default();
+        if let Some(settings) = &self.settings {
+            masterok.apply_settings(settings);
+            app.insert_resource(FixedGalaxy);
+        }
+
+        //the galaxy view is always there, GalaxySystemViewPlugin adds the other one
+        app.init_state::<ViewMode>()
+            .init_resource::<ErrorToasts>()
+            .insert_resource(masterok)
+            .insert_resource(self.star_data.clone())
+            .init_resource::<SettingsHistory>()
+            .init_resource::<CameraLimits>()
+            .init_resource::<UiFocus>()
+            .init_resource::<SelectedStar>()
+            .init_resource::<StarGrid>()
+            .init_resource::<HoverInfo>()
+            .init_resource::<CatalogExport>()
+            .add_event::<SpawnStars>()
+            .add_event::<StarsAdded>()
+            .add_event::<StarsRemoved>()
+            .add_event::<ChangeSeed>()
+            .add_event::<RespawnStars>()
+            .add_event::<GalaxyGenerated>()
+            .add_event::<StarSelected>()
+            .add_event::<ExportCatalog>()
+            .add_event::<FlyToStar>()
+            .add_event::<AppQuitting>()
+            .configure_sets(Update, GalaxySet::Input.before(smooth_camera_system))
+            .add_systems(Startup, request_initial_stars)
+            .add_systems(PostUpdate, spawn_initial_stars)
+            //chained so star_adder sees each count change in the frame star_watcher made it
+            .add_systems(
+                Update,
+                (
+                    replay_history_steps,
+                    star_watcher,
+                    star_adder,
+                    star_remover,
+                    despawn_all_stars,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    attach_camera_controller,
+                    update_camera_limits,
+                    fly_to_star_system.run_if(in_state(ViewMode::Galaxy)),
+                    smooth_camera_system,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                rebuild_star_grid.after(star_adder).after(star_remover),
+            )
+            .add_systems(
+                Update,
+                update_hover_info
+                    .after(rebuild_star_grid)
+                    .after(smooth_camera_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    clear_selection_on_regenerate.in_set(GalaxySet::Regenerated),
+                    highlight_selected_star.run_if(in_state(ViewMode::Galaxy)),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, export_catalog_system)
+            .add_systems(Last, (record_settings_history, finish_quit));
+
+        if self.camera == CameraOwnership::Spawn {
+            app.add_systems(Startup, spawn_galaxy_camera);
+        }
+
+        embedded_asset!(app, "star.png"); //embedding assets to exe
+    }
+}
+
+//adds a plugin another one depends on, unless the app already did
+fn require<P: Plugin>(app: &mut App, plugin: P) {
+    if !app.is_plugin_added::<P>() {
+        app.add_plugins(plugin);
+    }
+}
+
+//star lanes from the delaunay triangulation of the stars, routes, factions, exploration and legends
+//travel along them
+pub struct GalaxyLanesPlugin;
+
+impl Plugin for GalaxyLanesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LaneSettings>()
+            .init_resource::<LaneGraph>()
+            .init_resource::<StarLanes>()
+            .add_systems(
+                Update,
+                (rebuild_star_lanes, fade_star_lanes)
+                    .chain()
+                    .after(star_adder)
+                    .after(star_remover),
+            )
+            .add_panel_section(PanelSlot::Lanes, lanes_section);
+    }
+}
+
+//shortest routes between two stars along the lanes
+pub struct GalaxyRoutesPlugin;
+
+impl Plugin for GalaxyRoutesPlugin {
+    fn build(&self, app: &mut App) {
+        require(app, GalaxyLanesPlugin);
+        app.init_resource::<RouteRequest>()
+            .init_resource::<PlannedRoute>()
+            .add_systems(
+                Update,
+                (
+                    clear_route_on_regenerate.in_set(GalaxySet::Regenerated),
+                    plan_route_system,
+                )
+                    .chain()
+                    .after(rebuild_star_grid)
+                    .after(rebuild_star_lanes),
+            )
+            .add_systems(Update, draw_route.run_if(in_state(ViewMode::Galaxy)))
+            .add_panel_section(PanelSlot::Route, route_section);
+    }
+}
+
+//resources of every star, by class and region of the galaxy
+pub struct GalaxyEconomyPlugin;
+
+impl Plugin for GalaxyEconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StarEconomy>().add_systems(
+            Update,
+            rebuild_star_economy.after(star_adder).after(star_remover),
+        );
+    }
+}
+
+//factions growing from their capitals along the lanes
+pub struct GalaxyFactionsPlugin;
+
+impl Plugin for GalaxyFactionsPlugin {
+    fn build(&self, app: &mut App) {
+        require(app, GalaxyLanesPlugin);
+        app.init_resource::<FactionSettings>()
+            .init_resource::<Territories>()
+            .add_systems(
+                Update,
+                (
+                    clear_capitals_on_regenerate.in_set(GalaxySet::Regenerated),
+                    rebuild_territories,
+                )
+                    .chain()
+                    .after(rebuild_star_grid)
+                    .after(rebuild_star_lanes),
+            )
+            .add_panel_section(PanelSlot::Factions, factions_section);
+    }
+}
+
+//territories, resource heatmaps and the legends history painted over the galaxy, whichever of them
+//has its plugin added
+pub struct GalaxyOverlayPlugin;
+
+impl Plugin for GalaxyOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlayMode>()
+            .add_systems(
+                Update,
+                (refresh_overlay, overlay_visibility)
+                    .chain()
+                    .after(rebuild_star_grid)
+                    .after(rebuild_star_lanes)
+                    .after(rebuild_star_economy)
+                    .after(rebuild_territories),
+            )
+            .add_panel_section(PanelSlot::Overlay, overlay_section);
+    }
+}
+
+//planetary systems, generated from the seed for each star the player selects
+pub struct GalaxyPlanetsPlugin;
+
+impl Plugin for GalaxyPlanetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemCache>().add_systems(
+            Update,
+            (clear_system_cache, generate_selected_system)
+                .chain()
+                .after(clear_selection_on_regenerate),
+        );
+    }
+}
+
+//the selected star's planets on their orbits
+pub struct GalaxySystemViewPlugin;
+
+impl Plugin for GalaxySystemViewPlugin {
+    fn build(&self, app: &mut App) {
+        require(app, GalaxyPlanetsPlugin);
+        app.add_systems(
+            Update,
+            system_view_input
+                .in_set(GalaxySet::Input)
+                .run_if(resource_exists::<ActionState>),
+        )
+        .add_systems(OnEnter(ViewMode::System), enter_system_view)
+        .add_systems(OnExit(ViewMode::System), exit_system_view)
+        .add_systems(
+            Update,
+            (
+                leave_system_view_on_change,
+                animate_orbits,
+                draw_system_orbits,
+            )
+                .run_if(in_state(ViewMode::System)),
+        )
+        .add_panel_section(PanelSlot::SystemView, system_view_section);
+    }
+}
+
+//preferences and save slots, the parts that write to disk (or localStorage on the web) on their own
+pub struct GalaxyPersistencePlugin;
+
+impl Plugin for GalaxyPersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Preferences>()
+            .init_resource::<SaveSlots>()
+            .init_resource::<PendingLoad>()
+            .add_event::<SaveToSlot>()
+            .add_event::<LoadFromSlot>()
+            .add_systems(
+                Startup,
+                (
+                    load_preferences.before(request_initial_stars),
+                    //an explicit galaxy from the app wins over the one remembered from last time
+                    restore_last_galaxy
+                        .run_if(not(resource_exists::<FixedGalaxy>))
+                        .after(load_preferences)
+                        .before(request_initial_stars),
+                    scan_save_slots,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_preferences,
+                    apply_bloom_preference,
+                    save_to_slot_system,
+                    load_from_slot_system,
+                    finish_pending_load.after(GalaxySet::Regenerated),
+                ),
+            )
+            //sees AppQuitting from the ui in the same frame it quits
+            .add_systems(Last, save_preferences.before(finish_quit))
+            .add_panel_section(PanelSlot::Saves, saves_section)
+            .add_panel_section(PanelSlot::Display, display_section);
+    }
+}
+
+//the exploration game mode with its fog of war, progress is saved per galaxy
+pub struct GalaxyExplorationPlugin;
+
+impl Plugin for GalaxyExplorationPlugin {
+    fn build(&self, app: &mut App) {
+        require(app, GalaxyLanesPlugin);
+        app.init_resource::<Exploration>()
+            .init_resource::<ExploredStars>()
+            .add_systems(
+                Update,
+                (
+                    load_exploration_on_regenerate.in_set(GalaxySet::Regenerated),
+                    update_explored_stars,
+                    apply_fog_of_war,
+                    save_exploration,
+                )
+                    .chain()
+                    .after(rebuild_star_grid)
+                    .after(rebuild_star_lanes),
+            )
+            .add_systems(Update, draw_sensor_range.run_if(in_state(ViewMode::Galaxy)))
+            .add_panel_section(PanelSlot::Exploration, exploration_section);
+    }
+}
+
+//named views saved per galaxy, and share codes that carry them along with the galaxy
+pub struct GalaxyBookmarksPlugin;
+
+impl Plugin for GalaxyBookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bookmarks>()
+            .init_resource::<IncomingBookmarks>()
+            .init_resource::<BookmarkDraft>()
+            .init_resource::<ShareCodes>()
+            .add_event::<AddBookmark>()
+            .add_event::<JumpToBookmark>()
+            .add_event::<OpenShareCode>()
+            .add_systems(
+                Update,
+                bookmark_keys_system
+                    .in_set(GalaxySet::Input)
+                    .run_if(resource_exists::<ActionState>)
+                    .run_if(in_state(ViewMode::Galaxy)),
+            )
+            .add_systems(
+                Update,
+                (
+                    add_bookmark_system,
+                    jump_to_bookmark_system.run_if(in_state(ViewMode::Galaxy)),
+                )
+                    .chain()
+                    .after(update_camera_limits)
+                    .before(smooth_camera_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_bookmarks_on_regenerate.in_set(GalaxySet::Regenerated),
+                    save_bookmarks,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, open_share_code_system)
+            .add_panel_section(PanelSlot::Bookmarks, bookmarks_section)
+            .add_panel_section(PanelSlot::Share, share_section);
+    }
+}
+
+//the procedural history of the galaxy, simulated whenever the lanes or the economy change
+pub struct GalaxyLegendsPlugin;
+
+impl Plugin for GalaxyLegendsPlugin {
+    fn build(&self, app: &mut App) {
+        require(app, GalaxyLanesPlugin);
+        require(app, GalaxyEconomyPlugin);
+        app.init_resource::<Legends>()
+            .init_resource::<LegendsView>()
+            .init_resource::<LegendsExport>()
+            .add_event::<ExportLegends>()
+            .add_systems(
+                Update,
+                (rebuild_legends, update_legends_view)
+                    .chain()
+                    .after(rebuild_star_lanes)
+                    .after(rebuild_star_economy)
+                    .before(refresh_overlay),
+            )
+            .add_systems(Update, export_legends_system)
+            .add_panel_section(PanelSlot::Legends, legends_section);
+    }
+}
+
+//recorded camera paths, played back without the player
+pub struct GalaxyCinematicPlugin;
+
+impl Plugin for GalaxyCinematicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraPath>()
+            .init_resource::<Cinematic>()
+            .add_event::<AddKeyframe>()
+            .add_systems(
+                Update,
+                camera_path_keys_system
+                    .in_set(GalaxySet::Input)
+                    .run_if(resource_exists::<ActionState>)
+                    .run_if(in_state(ViewMode::Galaxy)),
+            )
+            .add_systems(
+                Update,
+                (record_camera_path, play_camera_path)
+                    .chain()
+                    .after(GalaxySet::Input)
+                    .after(update_camera_limits)
+                    .before(smooth_camera_system)
+                    .run_if(in_state(ViewMode::Galaxy)),
+            )
+            .add_panel_section(PanelSlot::CameraPath, camera_path_section);
+    }
+}
+
+//for unattended screens, drifts over the galaxy and cycles through random ones once nobody touches
+//anything, and puts the player's galaxy back when they do
+pub struct GalaxyAttractPlugin;
+
+impl Plugin for GalaxyAttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractMode>()
+            .init_resource::<AttractSettings>()
+            .add_systems(
+                Update,
+                (detect_user_input, run_attract_mode)
+                    .chain()
+                    .after(update_camera_limits)
+                    .before(smooth_camera_system)
+                    .run_if(in_state(ViewMode::Galaxy)),
+            )
+            .add_systems(Update, restore_attract_layers.after(GalaxySet::Regenerated))
+            .add_panel_section(PanelSlot::Attract, attract_section);
+    }
+}
+
+//keyboard, mouse, touch and gamepad controls
+pub struct GalaxyInputPlugin;
+
+impl Plugin for GalaxyInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<GamepadMap>()
+            .init_resource::<PointerDrag>()
+            .init_resource::<QuitDialog>()
+            .add_systems(Startup, load_key_map)
+            .add_systems(
+                PreUpdate,
+                (update_action_state, quit_request_system)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(
+                Update,
+                (
+                    //galaxy editing and camera keys do nothing while a system is shown
+                    history_input_system.run_if(in_state(ViewMode::Galaxy)),
+                    keyboard_input_system.run_if(in_state(ViewMode::Galaxy)),
+                    mouse_camera_system,
+                    pick_star_system.run_if(in_state(ViewMode::Galaxy)),
+                    touch_camera_system,
+                )
+                    .chain()
+                    .in_set(GalaxySet::Input),
+            );
+    }
+}
+
+//the side panel with the ratatui info view and the egui controls, other plugins add their own sections to it
+pub struct GalaxyUiPlugin;
+
+impl Plugin for GalaxyUiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+
+        app.init_resource::<KeyMap>()
+            .init_resource::<QuitDialog>()
+            .init_resource::<StarSearch>()
+            .init_resource::<PanelState>()
+            .add_systems(PreUpdate, update_ui_focus.after(EguiSet::BeginFrame))
+            .add_systems(
+                Update,
+                toggle_panel_system
+                    .in_set(GalaxySet::Input)
+                    .run_if(resource_exists::<ActionState>),
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_settings_draft,
+                    draw_info_terminal,
+                    side_panel_system,
+                    attract_fade_system.run_if(resource_exists::<AttractMode>),
+                    quit_dialog_system,
+                    error_toast_system,
+                )
+                    .chain(),
+            )
+            .add_panel_section(PanelSlot::SelectedStar, selected_star_section)
+            .add_panel_section(PanelSlot::Search, search_section)
+            .add_panel_section(PanelSlot::Settings, settings_section)
+            .add_panel_section(PanelSlot::History, history_section)
+            .add_panel_section(PanelSlot::Catalog, catalog_section);
+    }
+
+    //the draft copies the galaxy resources, so it waits until every plugin has been built
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<BevyTerminal<RataguiBackend>>()
+            .init_resource::<SettingsDraft>();
+    }
+}
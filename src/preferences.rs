@@ -0,0 +1,184 @@
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::attract::{AttractMode, AttractSettings};
+use crate::camera::GalaxyCamera;
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::quit::AppQuitting;
+use crate::resources::{GalaxySettings, Masterik, StarData};
+use crate::storage;
+use crate::ui::PanelState;
+
+//sliders fire every frame while dragged, so writes wait until things settle for this long
+const SAVE_DELAY_SECONDS: f32 = 1.0;
+
+//small things remembered between runs, written automatically, unlike save slots
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub camera_move_speed: f32,
+    //the galaxy on screen when the app was last closed, regenerated on the next start
+    pub last_galaxy: Option<GalaxySettings>,
+    pub star_data: Option<StarData>,
+    pub bloom_intensity: f32,
+    pub panel_visible: bool,
+    pub attract: AttractSettings,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            camera_move_speed: Masterik::default().camera_move_speed,
+            last_galaxy: None,
+            star_data: None,
+            bloom_intensity: 0.75,
+            panel_visible: true,
+            attract: AttractSettings::default(),
+        }
+    }
+}
+
+impl Preferences {
+    pub const STORAGE_KEY: &'static str = "preferences";
+
+    pub fn load() -> Result<Self, GalaxyError> {
+        let Some(text) = storage::read_text(Self::STORAGE_KEY).map_err(GalaxyError::Storage)?
+        else {
+            return Ok(Self::default());
+        };
+        let preferences: Self = ron::from_str(&text)
+            .map_err(|err| GalaxyError::Config(format!("preferences: {}", err)))?;
+
+        //the remembered galaxy is regenerated on start, so a bad one would panic on every launch
+        let galaxy = preferences
+            .last_galaxy
+            .as_ref()
+            .map_or(Ok(()), GalaxySettings::validate);
+        let star_data = preferences
+            .star_data
+            .as_ref()
+            .map_or(Ok(()), StarData::validate);
+        galaxy
+            .and(star_data)
+            .map_err(|err| GalaxyError::Config(format!("preferences: {}", err)))?;
+        Ok(preferences)
+    }
+
+    pub fn save(&self) -> Result<(), GalaxyError> {
+        let text = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| GalaxyError::Config(format!("preferences: {}", err)))?;
+        storage::write_text(Self::STORAGE_KEY, &text).map_err(GalaxyError::Storage)
+    }
+}
+
+//a broken preferences file is reported and then overwritten with working ones
+//the panel and attract mode belong to plugins of their own, they only pick up what is there
+pub fn load_preferences(
+    mut preferences: ResMut<Preferences>,
+    mut masterok: ResMut<Masterik>,
+    panel: Option<ResMut<PanelState>>,
+    attract: Option<ResMut<AttractSettings>>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    match Preferences::load() {
+        Ok(loaded) => *preferences = loaded,
+        Err(err) => toasts.report(&err),
+    }
+    masterok.camera_move_speed = preferences.camera_move_speed;
+    if let Some(mut panel) = panel {
+        panel.visible = preferences.panel_visible;
+    }
+    if let Some(mut attract) = attract {
+        *attract = preferences.attract.clone();
+    }
+}
+
+//only added when the app did not pick a galaxy itself
+pub fn restore_last_galaxy(
+    preferences: Res<Preferences>,
+    mut masterok: ResMut<Masterik>,
+    mut star_data: ResMut<StarData>,
+) {
+    if let Some(settings) = &preferences.last_galaxy {
+        masterok.apply_settings(settings);
+    }
+    if let Some(saved) = &preferences.star_data {
+        *star_data = saved.clone();
+    }
+}
+
+//copies the live state into the preferences, set_if_neq keeps them unchanged when nothing moved
+pub fn sync_preferences(
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    panel: Option<Res<PanelState>>,
+    attract: Option<Res<AttractMode>>,
+    attract_settings: Option<Res<AttractSettings>>,
+    mut preferences: ResMut<Preferences>,
+) {
+    //half generated galaxies are not worth remembering, neither are the ones attract mode cycles through
+    if masterok.block_input || attract.is_some_and(|attract| attract.is_running()) {
+        return;
+    }
+
+    let mut synced = preferences.clone();
+    synced.camera_move_speed = masterok.camera_move_speed;
+    synced.last_galaxy = Some(masterok.settings());
+    synced.star_data = Some(star_data.clone());
+    if let Some(panel) = panel {
+        synced.panel_visible = panel.visible;
+    }
+    if let Some(attract_settings) = attract_settings {
+        synced.attract = attract_settings.clone();
+    }
+    preferences.set_if_neq(synced);
+}
+
+pub fn save_preferences(
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    mut ev_quitting: EventReader<AppQuitting>,
+    mut toasts: ResMut<ErrorToasts>,
+    mut countdown: Local<Option<f32>>,
+) {
+    //the first change is the load itself
+    if preferences.is_changed() && !preferences.is_added() {
+        *countdown = Some(SAVE_DELAY_SECONDS);
+    }
+    let quitting = ev_quitting.read().count() > 0;
+
+    let Some(remaining) = countdown.as_mut() else {
+        return;
+    };
+    *remaining -= time.delta_seconds();
+    if *remaining > 0.0 && !quitting {
+        return;
+    }
+
+    *countdown = None;
+    if let Err(err) = preferences.save() {
+        toasts.report(&err);
+    }
+}
+
+//also catches the camera spawned after the preferences were loaded
+pub fn apply_bloom_preference(
+    preferences: Res<Preferences>,
+    mut query: Query<&mut BloomSettings, With<GalaxyCamera>>,
+) {
+    for mut bloom in query.iter_mut() {
+        if bloom.intensity != preferences.bloom_intensity {
+            bloom.intensity = preferences.bloom_intensity;
+        }
+    }
+}
+
+pub fn display_preferences_ui(ui: &mut egui::Ui, preferences: &mut Preferences) {
+    egui::CollapsingHeader::new("Display")
+        .id_source("display_preferences")
+        .show(ui, |ui| {
+            ui.add(egui::Slider::new(&mut preferences.bloom_intensity, 0.0..=2.0).text("Bloom"));
+        });
+}
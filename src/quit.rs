@@ -0,0 +1,82 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::input::{Action, ActionState};
+
+//sent once the player confirmed quitting, anything with unsaved state should flush it when reading this
+#[derive(Event)]
+pub struct AppQuitting;
+
+#[derive(Resource, Default)]
+pub struct QuitDialog {
+    pub open: bool,
+}
+
+//quitting only makes sense natively, the browser tab owns the lifetime of the web build
+pub const QUIT_SUPPORTED: bool = !cfg!(target_arch = "wasm32");
+
+//runs right after the actions are read, while the dialog is open it owns the keyboard:
+//enter confirms, escape cancels, and no other system sees those keys or any action
+pub fn quit_request_system(
+    input: Res<ButtonInput<KeyCode>>,
+    mut actions: ResMut<ActionState>,
+    mut dialog: ResMut<QuitDialog>,
+    mut ev_quitting: EventWriter<AppQuitting>,
+) {
+    if !dialog.open {
+        if QUIT_SUPPORTED && actions.just_pressed(Action::Quit) {
+            dialog.open = true;
+            actions.clear();
+        }
+        return;
+    }
+
+    if input.just_pressed(KeyCode::Enter) {
+        dialog.open = false;
+        ev_quitting.send(AppQuitting);
+    } else if input.just_pressed(KeyCode::Escape) {
+        dialog.open = false;
+    }
+    actions.clear();
+}
+
+//the keys are handled by quit_request_system, this only draws the dialog and its buttons
+pub fn quit_dialog_system(
+    mut contexts: EguiContexts,
+    mut dialog: ResMut<QuitDialog>,
+    mut ev_quitting: EventWriter<AppQuitting>,
+) {
+    if !dialog.open {
+        return;
+    }
+
+    let mut confirmed = false;
+    let mut cancelled = false;
+
+    egui::Window::new("Quit Kosmos?")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Close the galaxy viewer?");
+            ui.horizontal(|ui| {
+                confirmed |= ui.button("Quit [Enter]").clicked();
+                cancelled |= ui.button("Stay [Esc]").clicked();
+            });
+        });
+
+    if confirmed {
+        dialog.open = false;
+        ev_quitting.send(AppQuitting);
+    } else if cancelled {
+        dialog.open = false;
+    }
+}
+
+//runs after the flush systems so they see AppQuitting in the same frame
+pub fn finish_quit(mut ev_quitting: EventReader<AppQuitting>, mut ev_exit: EventWriter<AppExit>) {
+    if ev_quitting.read().count() > 0 {
+        ev_exit.send(AppExit);
+    }
+}
@@ -3,22 +3,34 @@ use bevy::prelude::*;
 use egui_ratatui::RataguiBackend;
 use rand::rngs::SmallRng;
 use ratatui::prelude::Terminal;
-use web_time::{Instant, SystemTime};
+use web_time::SystemTime;
 
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-//create resource to hold the ratatui terminal
+use crate::error::{ErrorToasts, GalaxyError};
+
+//create resource to hold the ratatui terminal, None if it could not be created
 #[derive(Resource)]
 pub struct BevyTerminal<RataguiBackend: ratatui::backend::Backend> {
-    pub terminal_info: Terminal<RataguiBackend>,
+    pub terminal_info: Option<Terminal<RataguiBackend>>,
 }
 
-// Implement default on the resource to initialize it
-impl Default for BevyTerminal<RataguiBackend> {
-    fn default() -> Self {
+// Initialize from the world so a failing terminal ends up as a toast instead of a panic
+impl FromWorld for BevyTerminal<RataguiBackend> {
+    fn from_world(world: &mut World) -> Self {
         let mut backend1 = RataguiBackend::new(20, 20);
         backend1.set_font_size(14);
-        let mut terminal1 = Terminal::new(backend1).unwrap();
+
+        let terminal1 = match Terminal::new(backend1) {
+            Ok(terminal) => Some(terminal),
+            Err(err) => {
+                world
+                    .get_resource_or_insert_with(ErrorToasts::default)
+                    .report(&GalaxyError::TerminalInit(err));
+                None
+            }
+        };
 
         BevyTerminal {
             terminal_info: terminal1,
@@ -57,15 +69,116 @@ impl Masterik {
 
         self.positions = Vec::new();
     }
+
+    //snapshot of everything that affects star generation
+    pub fn settings(&self) -> GalaxySettings {
+        GalaxySettings {
+            total_stars: self.total_stars,
+            gen_seed: self.gen_seed,
+            spiral_arm_count: self.spiral_arm_count,
+            o_class: self.o_class,
+            b_class: self.b_class,
+            a_class: self.a_class,
+            f_class: self.f_class,
+            g_class: self.g_class,
+            k_class: self.k_class,
+            m_class: self.m_class,
+            angle_mod: self.angle_mod,
+            radius_mod: self.radius_mod,
+            distance_mod: self.distance_mod,
+        }
+    }
+
+    //restores a snapshot and reseeds the rng so the same galaxy gets generated again, caller still has to send ChangeSeed
+    pub fn apply_settings(&mut self, settings: &GalaxySettings) {
+        self.total_stars = settings.total_stars;
+        self.gen_seed = settings.gen_seed;
+        self.spiral_arm_count = settings.spiral_arm_count;
+        self.o_class = settings.o_class;
+        self.b_class = settings.b_class;
+        self.a_class = settings.a_class;
+        self.f_class = settings.f_class;
+        self.g_class = settings.g_class;
+        self.k_class = settings.k_class;
+        self.m_class = settings.m_class;
+        self.angle_mod = settings.angle_mod;
+        self.radius_mod = settings.radius_mod;
+        self.distance_mod = settings.distance_mod;
+
+        self.rng = SmallRng::seed_from_u64(self.gen_seed);
+        self.positions = Vec::new();
+        self.block_input = true;
+    }
+}
+
+//the generation parameters of a galaxy without the runtime state, cheap to clone and compare
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GalaxySettings {
+    pub total_stars: i64,
+    pub gen_seed: u64,
+    pub spiral_arm_count: i64,
+    pub o_class: bool,
+    pub b_class: bool,
+    pub a_class: bool,
+    pub f_class: bool,
+    pub g_class: bool,
+    pub k_class: bool,
+    pub m_class: bool,
+    pub angle_mod: f32,
+    pub radius_mod: f32,
+    pub distance_mod: f32,
+}
+
+//what the settings panel lets you pick, keyboard steps can take the mods further so those only have to be positive
+pub const MIN_STARS: i64 = 1000;
+pub const MAX_STARS: i64 = 300000;
+pub const MAX_SPIRAL_ARMS: i64 = 4;
+
+impl GalaxySettings {
+    //settings from files and share codes go through this before they reach the generator, which panics on empty ranges
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_STARS..=MAX_STARS).contains(&self.total_stars) {
+            return Err(format!(
+                "{} stars, expected {} to {}",
+                self.total_stars, MIN_STARS, MAX_STARS
+            ));
+        }
+        if !(1..=MAX_SPIRAL_ARMS).contains(&self.spiral_arm_count) {
+            return Err(format!(
+                "{} spiral arms, expected 1 to {}",
+                self.spiral_arm_count, MAX_SPIRAL_ARMS
+            ));
+        }
+        //the radius is rolled from 2 up to radius_mod
+        let mods = [
+            ("angle mod", self.angle_mod, 0.0),
+            ("radius mod", self.radius_mod, 2.0),
+            ("distance mod", self.distance_mod, 0.0),
+        ];
+        for (name, value, above) in mods {
+            if !value.is_finite() || value <= above {
+                return Err(format!("{} {}, expected more than {}", name, value, above));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn time_seed() -> Result<u64, GalaxyError> {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .map_err(GalaxyError::Clock)
 }
 
 impl Default for Masterik {
     fn default() -> Self {
-        let ts = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let mut rng = SmallRng::seed_from_u64(ts.clone());
+        //a broken clock just means everyone gets the same first galaxy
+        let ts = time_seed().unwrap_or_else(|err| {
+            warn!("{}", err);
+            0
+        });
+        let mut rng = SmallRng::seed_from_u64(ts);
         let seed: u64 = rng.gen_range(1000..9000000000);
 
         let real_rng = SmallRng::seed_from_u64(seed);
@@ -93,8 +206,15 @@ impl Default for Masterik {
     }
 }
 
+//limits of the class radii and rarities, the rarity roll is out of a million
+pub const MIN_CLASS_RADIUS: f32 = 10.0;
+pub const MAX_CLASS_RADIUS: f32 = 50000.0;
+pub const MAX_RARITY: i64 = 1000000;
+//from_radius adds 10 to every radius before comparing, neighbouring classes need more room than that
+pub const CLASS_RADIUS_GAP: f32 = 20.0;
+
 //https://en.wikipedia.org/wiki/Stellar_classification#Harvard_spectral_classification
-#[derive(Resource)]
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StarData {
     pub o_class_radius: f32,
     pub b_class_radius: f32,
@@ -133,6 +253,160 @@ impl Default for StarData {
     }
 }
 
+impl StarData {
+    pub fn radii(&self) -> [f32; 7] {
+        [
+            self.o_class_radius,
+            self.b_class_radius,
+            self.a_class_radius,
+            self.f_class_radius,
+            self.g_class_radius,
+            self.k_class_radius,
+            self.m_class_radius,
+        ]
+    }
+
+    pub fn rarities(&self) -> [i64; 7] {
+        [
+            self.o_class_rarity,
+            self.b_class_rarity,
+            self.a_class_rarity,
+            self.f_class_rarity,
+            self.g_class_rarity,
+            self.k_class_rarity,
+            self.m_class_rarity,
+        ]
+    }
+
+    //same rules as the star data grid, hotter classes need bigger radii and rarer rolls
+    pub fn validate(&self) -> Result<(), String> {
+        let radii = self.radii();
+        if let Some(radius) = radii
+            .iter()
+            .find(|radius| !(MIN_CLASS_RADIUS..=MAX_CLASS_RADIUS).contains(*radius))
+        {
+            return Err(format!(
+                "class radius {}, expected {} to {}",
+                radius, MIN_CLASS_RADIUS, MAX_CLASS_RADIUS
+            ));
+        }
+        if radii
+            .windows(2)
+            .any(|pair| pair[0] - pair[1] < CLASS_RADIUS_GAP)
+        {
+            return Err("class radii have to shrink from O to M".into());
+        }
+
+        let rarities = self.rarities();
+        if rarities
+            .iter()
+            .any(|rarity| !(0..=MAX_RARITY).contains(rarity))
+        {
+            return Err(format!("class rarities have to be 0 to {}", MAX_RARITY));
+        }
+        if rarities.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("class rarities have to grow from O to M".into());
+        }
+        Ok(())
+    }
+}
+
+//harvard spectral classes, hottest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StarClass {
+    O,
+    B,
+    A,
+    F,
+    G,
+    K,
+    M,
+}
+
+impl StarClass {
+    pub const ALL: [StarClass; 7] = [
+        StarClass::O,
+        StarClass::B,
+        StarClass::A,
+        StarClass::F,
+        StarClass::G,
+        StarClass::K,
+        StarClass::M,
+    ];
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            StarClass::O => "O",
+            StarClass::B => "B",
+            StarClass::A => "A",
+            StarClass::F => "F",
+            StarClass::G => "G",
+            StarClass::K => "K",
+            StarClass::M => "M",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            StarClass::O => "Blue Giant",
+            StarClass::B => "Blue-White",
+            StarClass::A => "White",
+            StarClass::F => "Yellow-White Dwarf",
+            StarClass::G => "Sun",
+            StarClass::K => "Orange Dwarf",
+            StarClass::M => "Red Dwarf",
+        }
+    }
+
+    //stars only store their radius, the class radii from StarData map it back to a class
+    pub fn from_radius(radius: f32, star_data: &StarData) -> StarClass {
+        //buffer radius for testing to avoid floating point errors
+        let test_radius = radius + 10.0;
+
+        if test_radius > star_data.o_class_radius {
+            StarClass::O
+        } else if test_radius > star_data.b_class_radius {
+            StarClass::B
+        } else if test_radius > star_data.a_class_radius {
+            StarClass::A
+        } else if test_radius > star_data.f_class_radius {
+            StarClass::F
+        } else if test_radius > star_data.g_class_radius {
+            StarClass::G
+        } else if test_radius > star_data.k_class_radius {
+            StarClass::K
+        } else {
+            StarClass::M
+        }
+    }
+
+    //whether the class is switched on in the current settings
+    pub fn enabled(&self, masterok: &Masterik) -> bool {
+        match self {
+            StarClass::O => masterok.o_class,
+            StarClass::B => masterok.b_class,
+            StarClass::A => masterok.a_class,
+            StarClass::F => masterok.f_class,
+            StarClass::G => masterok.g_class,
+            StarClass::K => masterok.k_class,
+            StarClass::M => masterok.m_class,
+        }
+    }
+
+    pub fn toggle(&self, masterok: &mut Masterik) {
+        let flag = match self {
+            StarClass::O => &mut masterok.o_class,
+            StarClass::B => &mut masterok.b_class,
+            StarClass::A => &mut masterok.a_class,
+            StarClass::F => &mut masterok.f_class,
+            StarClass::G => &mut masterok.g_class,
+            StarClass::K => &mut masterok.k_class,
+            StarClass::M => &mut masterok.m_class,
+        };
+        *flag = !*flag;
+    }
+}
+
 #[derive(Event)]
 pub struct SpawnStars(pub i64);
 
@@ -147,7 +421,30 @@ pub struct ChangeSeed;
 #[derive(Event)]
 pub struct RespawnStars;
 
+//sent once a galaxy has been fully spawned, star_count includes the backdrop stars
+#[derive(Event, Clone, Debug)]
+pub struct GalaxyGenerated {
+    pub seed: u64,
+    pub star_count: usize,
+}
+
+//sent when a star gets selected, None when the selection is cleared, holds the index into Masterik.positions
+#[derive(Event, Clone, Debug)]
+pub struct StarSelected(pub Option<usize>);
+
+//whether the pointer or keyboard belongs to a ui panel, written by the ui plugin and read by the controls
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub pointer_over_ui: bool,
+    //a text field has focus, typing into it must not trigger key bindings
+    pub keyboard_over_ui: bool,
+}
+
 #[derive(Component)]
 pub struct StarCount(pub i64); // component that gets added to stars, remembers the order in which it was spawned
 
+//index of the sprite's star in Masterik::positions
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StarIndex(pub usize);
+
 pub type PositionsVec = Vec<(f32, f32, f32)>; // x y radius
@@ -0,0 +1,394 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::lanes::StarLanes;
+use crate::resources::{GalaxyGenerated, Masterik, StarClass, StarData};
+use crate::selection::StarInfo;
+use crate::spatial::StarGrid;
+
+//landing somewhere without fuel makes a jump this much more expensive than its length
+const NON_PREFERRED_PENALTY: f32 = 0.5;
+
+//what the player asked for, the route gets replanned whenever this changes
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RouteRequest {
+    pub origin: Option<usize>,
+    pub destination: Option<usize>,
+    //longest single jump, as a multiple of the median lane length
+    pub max_jump_factor: f32,
+    //only travel along lanes instead of jumping to anything in range
+    pub use_lanes: bool,
+    //classes with fuel, the planner prefers stopping at these
+    pub refuel_classes: Vec<StarClass>,
+}
+
+impl Default for RouteRequest {
+    fn default() -> Self {
+        Self {
+            origin: None,
+            destination: None,
+            max_jump_factor: 3.0,
+            use_lanes: true,
+            refuel_classes: vec![StarClass::F, StarClass::G, StarClass::K],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub stars: Vec<usize>,
+    pub distance: f32,
+    pub refuel_stops: usize,
+}
+
+impl Route {
+    pub fn jumps(&self) -> usize {
+        self.stars.len().saturating_sub(1)
+    }
+}
+
+//result of the last planning, None with both ends set means there is no way through
+#[derive(Resource, Default)]
+pub struct PlannedRoute {
+    pub route: Option<Route>,
+}
+
+//open set entry, ordered so the binary heap pops the lowest estimate first
+struct Candidate {
+    estimate: f32,
+    star: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+fn position(positions: &[(f32, f32, f32)], star: usize) -> Vec2 {
+    let (x, y, _) = positions[star];
+    Vec2::new(x, y)
+}
+
+//a* with the straight line distance as the heuristic, which never overestimates since jumps cost at least their length
+pub fn plan_route(
+    request: &RouteRequest,
+    positions: &[(f32, f32, f32)],
+    star_data: &StarData,
+    lanes: &StarLanes,
+    grid: &StarGrid,
+) -> Option<Route> {
+    let (origin, destination) = (request.origin?, request.destination?);
+    if origin >= positions.len() || destination >= positions.len() {
+        return None;
+    }
+
+    let max_jump = lanes.median_length * request.max_jump_factor;
+    let goal = position(positions, destination);
+    let refuels = |star: usize| {
+        request
+            .refuel_classes
+            .contains(&StarClass::from_radius(positions[star].2, star_data))
+    };
+    let neighbors = |star: usize| -> Vec<usize> {
+        let here = position(positions, star);
+        let candidates = if request.use_lanes {
+            lanes.adjacency.get(star).cloned().unwrap_or_default()
+        } else {
+            grid.within(positions, here, max_jump)
+        };
+        candidates
+            .into_iter()
+            .filter(|&next| next != star && position(positions, next).distance(here) <= max_jump)
+            .collect()
+    };
+
+    let mut cost: HashMap<usize, f32> = HashMap::from([(origin, 0.0)]);
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut open = BinaryHeap::from([Candidate {
+        estimate: position(positions, origin).distance(goal),
+        star: origin,
+    }]);
+
+    while let Some(Candidate { estimate, star }) = open.pop() {
+        if star == destination {
+            break;
+        }
+        let here_cost = cost[&star];
+        //stale heap entry, a cheaper way here was found after it was pushed
+        if estimate > here_cost + position(positions, star).distance(goal) {
+            continue;
+        }
+
+        for next in neighbors(star) {
+            let length = position(positions, star).distance(position(positions, next));
+            let penalty = if next == destination || refuels(next) {
+                1.0
+            } else {
+                1.0 + NON_PREFERRED_PENALTY
+            };
+            let next_cost = here_cost + length * penalty;
+            if cost.get(&next).map_or(true, |&known| next_cost < known) {
+                cost.insert(next, next_cost);
+                came_from.insert(next, star);
+                open.push(Candidate {
+                    estimate: next_cost + position(positions, next).distance(goal),
+                    star: next,
+                });
+            }
+        }
+    }
+
+    if !cost.contains_key(&destination) {
+        return None;
+    }
+
+    let mut stars = vec![destination];
+    while let Some(&previous) = came_from.get(stars.last()?) {
+        stars.push(previous);
+    }
+    stars.reverse();
+
+    let distance = stars
+        .windows(2)
+        .map(|pair| position(positions, pair[0]).distance(position(positions, pair[1])))
+        .sum();
+    //the origin and destination are not stops
+    let refuel_stops = stars
+        .iter()
+        .skip(1)
+        .take(stars.len().saturating_sub(2))
+        .filter(|&&star| refuels(star))
+        .count();
+
+    Some(Route {
+        stars,
+        distance,
+        refuel_stops,
+    })
+}
+
+pub fn plan_route_system(
+    request: Res<RouteRequest>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    lanes: Res<StarLanes>,
+    grid: Res<StarGrid>,
+    mut planned: ResMut<PlannedRoute>,
+) {
+    if masterok.block_input || !(request.is_changed() || lanes.is_changed()) {
+        return;
+    }
+    planned.route = plan_route(&request, &masterok.positions, &star_data, &lanes, &grid);
+}
+
+//the stars behind the indices are gone after a regeneration
+pub fn clear_route_on_regenerate(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut request: ResMut<RouteRequest>,
+) {
+    if ev_generated.read().count() > 0 {
+        request.origin = None;
+        request.destination = None;
+    }
+}
+
+pub fn draw_route(planned: Res<PlannedRoute>, masterok: Res<Masterik>, mut gizmos: Gizmos) {
+    let Some(route) = &planned.route else {
+        return;
+    };
+    let points = route
+        .stars
+        .iter()
+        .filter_map(|&star| masterok.positions.get(star))
+        .map(|&(x, y, _)| Vec2::new(x, y));
+    gizmos.linestrip_2d(points, Color::rgb(1.0, 0.8, 0.2));
+}
+
+fn star_label(star: Option<usize>, masterok: &Masterik, star_data: &StarData) -> String {
+    star.and_then(|star| StarInfo::new(star, masterok, star_data))
+        .map_or("-".to_string(), |info| info.name)
+}
+
+pub fn route_ui(
+    ui: &mut egui::Ui,
+    request: &mut RouteRequest,
+    planned: &PlannedRoute,
+    selected: Option<usize>,
+    masterok: &Masterik,
+    star_data: &StarData,
+    median_lane_length: f32,
+) {
+    egui::CollapsingHeader::new("Route")
+        .id_source("star_route")
+        .show(ui, |ui| {
+            egui::Grid::new("star_route_grid")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.label("From");
+                    ui.label(star_label(request.origin, masterok, star_data));
+                    if ui
+                        .add_enabled(selected.is_some(), egui::Button::new("Selected"))
+                        .clicked()
+                    {
+                        request.origin = selected;
+                    }
+                    ui.end_row();
+
+                    ui.label("To");
+                    ui.label(star_label(request.destination, masterok, star_data));
+                    if ui
+                        .add_enabled(selected.is_some(), egui::Button::new("Selected"))
+                        .clicked()
+                    {
+                        request.destination = selected;
+                    }
+                    ui.end_row();
+                });
+
+            ui.checkbox(&mut request.use_lanes, "Follow Lanes");
+            ui.add(
+                egui::Slider::new(&mut request.max_jump_factor, 0.5..=20.0)
+                    .text("Max Jump (x lane)"),
+            );
+            ui.small(format!(
+                "Max jump {:.0}",
+                median_lane_length * request.max_jump_factor
+            ));
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Refuel at");
+                for class in StarClass::ALL {
+                    let mut refuels = request.refuel_classes.contains(&class);
+                    if ui.checkbox(&mut refuels, class.letter()).changed() {
+                        if refuels {
+                            request.refuel_classes.push(class);
+                        } else {
+                            request.refuel_classes.retain(|&c| c != class);
+                        }
+                    }
+                }
+            });
+
+            if request.origin.is_none() || request.destination.is_none() {
+                return;
+            }
+            match &planned.route {
+                Some(route) => {
+                    ui.label(format!(
+                        "Distance {:.0}, {} jumps, {} refuel stops",
+                        route.distance,
+                        route.jumps(),
+                        route.refuel_stops
+                    ));
+                }
+                None => {
+                    ui.colored_label(egui::Color32::LIGHT_RED, "No route within jump range");
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lanes::{LaneGraph, LaneSettings};
+
+    //a row of stars 100 apart with a 5000 gap in the middle
+    fn row() -> Vec<(f32, f32, f32)> {
+        (0..20)
+            .map(|index| {
+                let gap = if index < 10 { 0.0 } else { 5000.0 };
+                let wobble = if index % 2 == 0 { 20.0 } else { -20.0 };
+                (index as f32 * 100.0 + gap, wobble, 1000.0)
+            })
+            .collect()
+    }
+
+    fn request(use_lanes: bool) -> RouteRequest {
+        RouteRequest {
+            origin: Some(0),
+            destination: Some(19),
+            use_lanes,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn follows_lanes_end_to_end() {
+        let positions = row();
+        let lanes = StarLanes::build(
+            &LaneGraph::triangulate(&positions).unwrap(),
+            &LaneSettings::default(),
+        );
+        let grid = StarGrid::build(&positions);
+
+        //long enough for the one lane across the gap
+        let route = plan_route(
+            &RouteRequest {
+                max_jump_factor: 100.0,
+                ..request(true)
+            },
+            &positions,
+            &StarData::default(),
+            &lanes,
+            &grid,
+        )
+        .unwrap();
+        assert_eq!(route.stars.first(), Some(&0));
+        assert_eq!(route.stars.last(), Some(&19));
+        for pair in route.stars.windows(2) {
+            assert!(lanes.adjacency[pair[0]].contains(&pair[1]));
+        }
+        assert!(route.distance >= position(&positions, 0).distance(position(&positions, 19)));
+    }
+
+    #[test]
+    fn no_route_when_a_jump_is_too_long() {
+        let positions = row();
+        let lanes = StarLanes::build(
+            &LaneGraph::triangulate(&positions).unwrap(),
+            &LaneSettings::default(),
+        );
+        let grid = StarGrid::build(&positions);
+
+        //the gap is far more than three median lanes
+        assert!(plan_route(
+            &request(false),
+            &positions,
+            &StarData::default(),
+            &lanes,
+            &grid,
+        )
+        .is_none());
+        assert!(plan_route(
+            &RouteRequest {
+                destination: Some(99),
+                ..request(true)
+            },
+            &positions,
+            &StarData::default(),
+            &lanes,
+            &grid,
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,534 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::camera::{CameraController, GalaxyCamera};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::exploration::Exploration;
+use crate::factions::FactionSettings;
+use crate::lanes::LaneSettings;
+use crate::legends::LegendsView;
+use crate::overlay::OverlayMode;
+use crate::resources::{
+    ChangeSeed, GalaxyGenerated, GalaxySettings, Masterik, StarData, StarSelected,
+};
+use crate::routes::RouteRequest;
+use crate::selection::SelectedStar;
+use crate::storage;
+
+//bump when the format changes and teach SaveGame::parse how to upgrade the previous one
+pub const SAVE_VERSION: u32 = 2;
+pub const SAVE_SLOTS: usize = 4;
+
+//read first to find out which format the rest of the file is in
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedCamera {
+    pub translation: Vec2,
+    pub scale: f32,
+}
+
+//everything the player layered on top of the generated galaxy
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserLayers {
+    pub selected: Option<usize>,
+    pub lanes: LaneSettings,
+    pub route: RouteRequest,
+    pub factions: FactionSettings,
+    pub overlay: OverlayMode,
+    pub legends_year: u32,
+    pub exploration: Exploration,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+//a whole session, the stars themselves are regenerated from the settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub settings: GalaxySettings,
+    pub camera_move_speed: f32,
+    pub star_data: StarData,
+    pub camera: Option<SavedCamera>,
+    pub layers: UserLayers,
+}
+
+//format 1, from before bookmarks
+#[derive(Deserialize)]
+struct UserLayersV1 {
+    selected: Option<usize>,
+    lanes: LaneSettings,
+    route: RouteRequest,
+    factions: FactionSettings,
+    overlay: OverlayMode,
+    legends_year: u32,
+    exploration: Exploration,
+}
+
+#[derive(Deserialize)]
+struct SaveGameV1 {
+    settings: GalaxySettings,
+    camera_move_speed: f32,
+    star_data: StarData,
+    camera: Option<SavedCamera>,
+    layers: UserLayersV1,
+}
+
+//bookmarks stored with the galaxy itself are kept when an old save without any is loaded
+impl From<SaveGameV1> for SaveGame {
+    fn from(old: SaveGameV1) -> Self {
+        let layers = old.layers;
+        Self {
+            version: 2,
+            settings: old.settings,
+            camera_move_speed: old.camera_move_speed,
+            star_data: old.star_data,
+            camera: old.camera,
+            layers: UserLayers {
+                selected: layers.selected,
+                lanes: layers.lanes,
+                route: layers.route,
+                factions: layers.factions,
+                overlay: layers.overlay,
+                legends_year: layers.legends_year,
+                exploration: layers.exploration,
+                bookmarks: Vec::new(),
+            },
+        }
+    }
+}
+
+impl SaveGame {
+    fn storage_key(slot: usize) -> String {
+        format!("save-{}", slot)
+    }
+
+    //older versions get an arm here that parses their own struct and converts it forward one
+    //version at a time, so every old format only ever needs to know about the next one
+    pub fn parse(text: &str) -> Result<Self, GalaxyError> {
+        let header: SaveHeader =
+            ron::from_str(text).map_err(|err| GalaxyError::Save(err.to_string()))?;
+        let save = match header.version {
+            SAVE_VERSION => ron::from_str(text).map_err(|err| GalaxyError::Save(err.to_string())),
+            1 => ron::from_str::<SaveGameV1>(text)
+                .map(SaveGame::from)
+                .map_err(|err| GalaxyError::Save(err.to_string())),
+            newer if newer > SAVE_VERSION => Err(GalaxyError::Save(format!(
+                "written by a newer version (format {})",
+                newer
+            ))),
+            older => Err(GalaxyError::Save(format!("unknown format {}", older))),
+        }?;
+
+        //save files can be edited by hand
+        save.settings
+            .validate()
+            .and_then(|()| save.star_data.validate())
+            .map_err(GalaxyError::Save)?;
+        Ok(save)
+    }
+
+    pub fn read(slot: usize) -> Result<Option<Self>, GalaxyError> {
+        storage::read_text(&Self::storage_key(slot))
+            .map_err(GalaxyError::Storage)?
+            .map(|text| Self::parse(&text))
+            .transpose()
+    }
+
+    pub fn write(&self, slot: usize) -> Result<(), GalaxyError> {
+        let text = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| GalaxyError::Save(err.to_string()))?;
+        storage::write_text(&Self::storage_key(slot), &text).map_err(GalaxyError::Storage)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Seed {}, {} stars, {} arms",
+            self.settings.gen_seed, self.settings.total_stars, self.settings.spiral_arm_count
+        )
+    }
+}
+
+//what each slot holds, read once at startup and after every save
+#[derive(Resource, Default)]
+pub struct SaveSlots {
+    pub slots: Vec<Option<String>>,
+}
+
+impl SaveSlots {
+    pub fn scan() -> Self {
+        let slots = (0..SAVE_SLOTS)
+            .map(|slot| match SaveGame::read(slot) {
+                Ok(save) => save.map(|save| save.summary()),
+                Err(err) => Some(err.to_string()),
+            })
+            .collect();
+        Self { slots }
+    }
+}
+
+#[derive(Event)]
+pub struct SaveToSlot(pub usize);
+
+#[derive(Event)]
+pub struct LoadFromSlot(pub usize);
+
+//a loaded save waits here until its galaxy has been generated, the layers point into its stars
+#[derive(Resource, Default)]
+pub struct PendingLoad {
+    pub save: Option<SaveGame>,
+}
+
+//the resources behind UserLayers, shared by saving and loading, the optional ones come from their own plugins
+#[derive(SystemParam)]
+pub struct SessionLayers<'w> {
+    pub selected: ResMut<'w, SelectedStar>,
+    pub lanes: Option<ResMut<'w, LaneSettings>>,
+    pub route: Option<ResMut<'w, RouteRequest>>,
+    pub factions: Option<ResMut<'w, FactionSettings>>,
+    pub overlay: Option<ResMut<'w, OverlayMode>>,
+    pub legends: Option<ResMut<'w, LegendsView>>,
+    pub exploration: Option<ResMut<'w, Exploration>>,
+    pub bookmarks: Option<ResMut<'w, Bookmarks>>,
+}
+
+impl SessionLayers<'_> {
+    pub fn capture(&self) -> UserLayers {
+        UserLayers {
+            selected: self.selected.index,
+            lanes: self.lanes.as_deref().cloned().unwrap_or_default(),
+            route: self.route.as_deref().cloned().unwrap_or_default(),
+            factions: self.factions.as_deref().cloned().unwrap_or_default(),
+            overlay: self.overlay.as_deref().copied().unwrap_or_default(),
+            legends_year: self.legends.as_ref().map_or(0, |view| view.year),
+            exploration: self.exploration.as_deref().cloned().unwrap_or_default(),
+            bookmarks: self
+                .bookmarks
+                .as_ref()
+                .map_or_else(Vec::new, |bookmarks| bookmarks.list.clone()),
+        }
+    }
+
+    pub fn restore(&mut self, layers: UserLayers, seed: u64) {
+        self.selected.index = layers.selected;
+        if let Some(lanes) = self.lanes.as_mut() {
+            lanes.set_if_neq(layers.lanes);
+        }
+        if let Some(route) = self.route.as_mut() {
+            route.set_if_neq(layers.route);
+        }
+        if let Some(factions) = self.factions.as_mut() {
+            factions.set_if_neq(layers.factions);
+        }
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.set_if_neq(layers.overlay);
+        }
+        if let Some(legends) = self.legends.as_mut() {
+            legends.year = layers.legends_year;
+        }
+        if let Some(exploration) = self.exploration.as_mut() {
+            **exploration = Exploration {
+                galaxy: Some(seed),
+                ..layers.exploration
+            };
+        }
+        //merged rather than replaced, the galaxy may have gained bookmarks since the save
+        if let Some(bookmarks) = self.bookmarks.as_mut() {
+            bookmarks.merge(layers.bookmarks);
+        }
+    }
+}
+
+pub fn scan_save_slots(mut slots: ResMut<SaveSlots>) {
+    *slots = SaveSlots::scan();
+}
+
+pub fn save_to_slot_system(
+    mut ev_save: EventReader<SaveToSlot>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    layers: SessionLayers,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<GalaxyCamera>>,
+    mut slots: ResMut<SaveSlots>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    let Some(SaveToSlot(slot)) = ev_save.read().last() else {
+        return;
+    };
+    if masterok.block_input {
+        return;
+    }
+
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        settings: masterok.settings(),
+        camera_move_speed: masterok.camera_move_speed,
+        star_data: star_data.clone(),
+        camera: camera_query
+            .get_single()
+            .ok()
+            .map(|(transform, projection)| SavedCamera {
+                translation: transform.translation.truncate(),
+                scale: projection.scale,
+            }),
+        layers: layers.capture(),
+    };
+    match save.write(*slot) {
+        Ok(()) => {
+            info!("saved to slot {}", slot + 1);
+            *slots = SaveSlots::scan();
+        }
+        Err(err) => toasts.report(&err),
+    }
+}
+
+//regenerates the saved galaxy, the rest is put back by finish_pending_load once it exists
+pub fn load_from_slot_system(
+    mut ev_load: EventReader<LoadFromSlot>,
+    mut masterok: ResMut<Masterik>,
+    mut star_data: ResMut<StarData>,
+    mut pending: ResMut<PendingLoad>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    let Some(LoadFromSlot(slot)) = ev_load.read().last() else {
+        return;
+    };
+    if masterok.block_input {
+        return;
+    }
+
+    match SaveGame::read(*slot) {
+        Ok(Some(save)) => {
+            masterok.apply_settings(&save.settings);
+            masterok.camera_move_speed = save.camera_move_speed;
+            star_data.set_if_neq(save.star_data.clone());
+            pending.save = Some(save);
+            ev_change_seed.send(ChangeSeed);
+        }
+        Ok(None) => (),
+        Err(err) => toasts.report(&err),
+    }
+}
+
+//runs after everything that resets itself on a new galaxy, otherwise it would wipe the restored layers
+pub fn finish_pending_load(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut pending: ResMut<PendingLoad>,
+    mut layers: SessionLayers,
+    mut ev_selected: EventWriter<StarSelected>,
+    mut camera_query: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Some(generated) = ev_generated.read().last() else {
+        return;
+    };
+    let Some(save) = pending.save.take() else {
+        return;
+    };
+
+    if let (Some(camera), Ok((mut transform, mut projection, mut controller))) =
+        (&save.camera, camera_query.get_single_mut())
+    {
+        transform.translation = camera.translation.extend(transform.translation.z);
+        projection.scale = camera.scale;
+        controller.target_scale = camera.scale;
+        controller.flight = None;
+    }
+
+    layers.restore(save.layers, generated.seed);
+    ev_selected.send(StarSelected(layers.selected.index));
+}
+
+pub enum SlotAction {
+    Save(usize),
+    Load(usize),
+}
+
+pub fn save_slots_ui(ui: &mut egui::Ui, slots: &SaveSlots) -> Option<SlotAction> {
+    let mut action = None;
+    egui::CollapsingHeader::new("Saves")
+        .id_source("save_slots")
+        .show(ui, |ui| {
+            egui::Grid::new("save_slots_grid")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for slot in 0..SAVE_SLOTS {
+                        let summary = slots.slots.get(slot).cloned().flatten();
+                        ui.label(format!("{}", slot + 1));
+                        ui.small(summary.as_deref().unwrap_or("Empty"));
+                        ui.horizontal(|ui| {
+                            if ui.button("Save").clicked() {
+                                action = Some(SlotAction::Save(slot));
+                            }
+                            if ui
+                                .add_enabled(summary.is_some(), egui::Button::new("Load"))
+                                .clicked()
+                            {
+                                action = Some(SlotAction::Load(slot));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lanes::LanePruning;
+
+    fn settings() -> GalaxySettings {
+        GalaxySettings {
+            total_stars: 30000,
+            gen_seed: 1234,
+            spiral_arm_count: 2,
+            o_class: true,
+            b_class: true,
+            a_class: true,
+            f_class: true,
+            g_class: true,
+            k_class: true,
+            m_class: true,
+            angle_mod: 0.00076,
+            radius_mod: 2200.0,
+            distance_mod: 60.0,
+        }
+    }
+
+    //a format 1 save as it was written before bookmarks existed
+    const SAVE_V1: &str = r#"(
+    version: 1,
+    settings: (
+        total_stars: 30000,
+        gen_seed: 1234,
+        spiral_arm_count: 2,
+        o_class: true,
+        b_class: true,
+        a_class: true,
+        f_class: true,
+        g_class: true,
+        k_class: true,
+        m_class: true,
+        angle_mod: 0.00076,
+        radius_mod: 2200.0,
+        distance_mod: 60.0,
+    ),
+    camera_move_speed: 10.0,
+    star_data: (
+        o_class_radius: 16000.0,
+        b_class_radius: 5000.0,
+        a_class_radius: 2000.0,
+        f_class_radius: 1500.0,
+        g_class_radius: 1000.0,
+        k_class_radius: 500.0,
+        m_class_radius: 100.0,
+        o_class_rarity: 30,
+        b_class_rarity: 2400,
+        a_class_rarity: 9000,
+        f_class_rarity: 30000,
+        g_class_rarity: 70000,
+        k_class_rarity: 120000,
+        m_class_rarity: 760000,
+    ),
+    camera: Some((
+        translation: (120.0, -40.0),
+        scale: 3.0,
+    )),
+    layers: (
+        selected: Some(7),
+        lanes: (
+            visible: true,
+            pruning: Gabriel,
+            max_length_factor: 2.5,
+        ),
+        route: (
+            origin: Some(7),
+            destination: Some(42),
+            max_jump_factor: 3.0,
+            use_lanes: true,
+            refuel_classes: [F, G, K],
+        ),
+        factions: (
+            count: 6,
+            capitals: [7],
+        ),
+        overlay: Territories,
+        legends_year: 250,
+        exploration: (
+            active: true,
+            hide_unexplored: false,
+            current: Some(7),
+            visited: [7, 12],
+        ),
+    ),
+)"#;
+
+    #[test]
+    fn upgrades_version_1() {
+        let save = SaveGame::parse(SAVE_V1).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.settings, settings());
+        assert_eq!(save.star_data, StarData::default());
+        assert_eq!(save.layers.selected, Some(7));
+        assert_eq!(save.layers.lanes.pruning, LanePruning::Gabriel);
+        assert_eq!(save.layers.route.destination, Some(42));
+        assert_eq!(save.layers.factions.capitals, vec![7]);
+        assert_eq!(save.layers.overlay, OverlayMode::Territories);
+        assert_eq!(save.layers.exploration.visited, vec![7, 12]);
+        assert!(save.layers.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn reads_back_the_current_version() {
+        let save = SaveGame {
+            version: SAVE_VERSION,
+            settings: settings(),
+            camera_move_speed: 10.0,
+            star_data: StarData::default(),
+            camera: None,
+            layers: UserLayers {
+                selected: Some(3),
+                lanes: LaneSettings::default(),
+                route: RouteRequest::default(),
+                factions: FactionSettings::default(),
+                overlay: OverlayMode::default(),
+                legends_year: 0,
+                exploration: Exploration::default(),
+                bookmarks: vec![Bookmark {
+                    name: "Core".into(),
+                    translation: Vec2::ZERO,
+                    scale: 2.0,
+                }],
+            },
+        };
+        let text = ron::ser::to_string_pretty(&save, Default::default()).unwrap();
+
+        let loaded = SaveGame::parse(&text).unwrap();
+        assert_eq!(loaded.settings, save.settings);
+        assert_eq!(loaded.layers.selected, Some(3));
+        assert_eq!(loaded.layers.bookmarks, save.layers.bookmarks);
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let text = SAVE_V1.replacen("version: 1", &format!("version: {}", SAVE_VERSION + 1), 1);
+        assert!(matches!(
+            SaveGame::parse(&text),
+            Err(GalaxyError::Save(message)) if message.contains("newer")
+        ));
+    }
+}
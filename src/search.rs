@@ -0,0 +1,300 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::camera::{CameraController, CameraLimits, GalaxyCamera};
+use crate::exploration::ExploredStars;
+use crate::resources::{Masterik, StarClass, StarData, StarSelected};
+use crate::selection::{SelectedStar, StarInfo};
+
+//more than this is not useful to scroll through, refine the query instead
+const MAX_RESULTS: usize = 200;
+//a star the camera flies to ends up about this many pixels wide
+const FLY_TO_STAR_PIXELS: f32 = 40.0;
+
+//flies the galaxy camera to a star and selects it
+#[derive(Event)]
+pub struct FlyToStar(pub usize);
+
+//what the filter looks at for one star, names are lowercased once per galaxy instead of per keystroke
+pub struct SearchEntry {
+    pub index: usize,
+    pub class: StarClass,
+    pub radius: f32,
+    pub name: String,
+    pub designation: String,
+}
+
+impl SearchEntry {
+    pub fn new(info: StarInfo) -> Self {
+        Self {
+            index: info.index,
+            class: info.class,
+            radius: info.radius,
+            name: info.name.to_lowercase(),
+            designation: info.designation.to_lowercase(),
+        }
+    }
+}
+
+//parsed search box contents, every part that is set has to match
+#[derive(Default, Debug, PartialEq)]
+pub struct StarFilter {
+    pub text: Vec<String>,
+    pub classes: Vec<StarClass>,
+    pub index: Option<usize>,
+    pub min_radius: Option<f32>,
+    pub max_radius: Option<f32>,
+}
+
+impl StarFilter {
+    //"vela class:OB radius>10000 #42", words are matched against names and designations
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+
+        for token in query.split_whitespace() {
+            let lower = token.to_lowercase();
+
+            if let Some(letters) = lower.strip_prefix("class:") {
+                for letter in letters.chars() {
+                    let class = StarClass::ALL
+                        .into_iter()
+                        .find(|class| class.letter().eq_ignore_ascii_case(&letter.to_string()))
+                        .ok_or_else(|| format!("unknown star class '{}'", letter))?;
+                    filter.classes.push(class);
+                }
+            } else if let Some(rest) = lower.strip_prefix("radius") {
+                let (bound, value) = if let Some(value) = rest.strip_prefix('>') {
+                    (&mut filter.min_radius, value)
+                } else if let Some(value) = rest.strip_prefix('<') {
+                    (&mut filter.max_radius, value)
+                } else {
+                    return Err(format!("expected radius>N or radius<N, got '{}'", token));
+                };
+                *bound = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a number", value))?,
+                );
+            } else if let Ok(index) = lower.trim_start_matches('#').parse() {
+                filter.index = Some(index);
+            } else {
+                filter.text.push(lower);
+            }
+        }
+
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, entry: &SearchEntry) -> bool {
+        if self.index.is_some_and(|index| index != entry.index)
+            || (!self.classes.is_empty() && !self.classes.contains(&entry.class))
+            || self.min_radius.is_some_and(|min| entry.radius <= min)
+            || self.max_radius.is_some_and(|max| entry.radius >= max)
+        {
+            return false;
+        }
+
+        self.text.iter().all(|word| {
+            entry.name.contains(word.as_str()) || entry.designation.contains(word.as_str())
+        })
+    }
+}
+
+//search box state, results are only recomputed when the query, the galaxy or the explored stars change
+#[derive(Resource, Default)]
+pub struct StarSearch {
+    pub query: String,
+    pub results: Vec<usize>,
+    pub total_matches: usize,
+    pub error: Option<String>,
+    //query, seed, star count and explored count the results belong to
+    searched: Option<(String, u64, usize, Option<usize>)>,
+    entries: Vec<SearchEntry>,
+    //seed and star classes the entries were built for
+    indexed: Option<(u64, StarData)>,
+}
+
+impl StarSearch {
+    //stars still in the fog are never found, not even by #index
+    pub fn refresh(
+        &mut self,
+        masterok: &Masterik,
+        star_data: &StarData,
+        explored: Option<&ExploredStars>,
+    ) {
+        let explored = explored.filter(|explored| explored.active);
+        let key = (
+            self.query.clone(),
+            masterok.gen_seed,
+            masterok.positions.len(),
+            explored.map(|explored| explored.count),
+        );
+        if masterok.block_input || self.searched.as_ref() == Some(&key) {
+            return;
+        }
+        self.searched = Some(key);
+        self.results.clear();
+        self.total_matches = 0;
+
+        let filter = match StarFilter::parse(&self.query) {
+            Ok(filter) => filter,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        self.error = None;
+        if filter.is_empty() {
+            return;
+        }
+
+        let indexed = (masterok.gen_seed, star_data.clone());
+        if self.indexed.as_ref() != Some(&indexed) || self.entries.len() != masterok.positions.len()
+        {
+            self.entries = (0..masterok.positions.len())
+                .filter_map(|index| StarInfo::new(index, masterok, star_data))
+                .map(SearchEntry::new)
+                .collect();
+            self.indexed = Some(indexed);
+        }
+
+        for entry in &self.entries {
+            if explored.is_some_and(|explored| !explored.reveals(entry.index)) {
+                continue;
+            }
+            if filter.matches(entry) {
+                self.total_matches += 1;
+                if self.results.len() < MAX_RESULTS {
+                    self.results.push(entry.index);
+                }
+            }
+        }
+    }
+}
+
+//returns the star the player picked from the results
+pub fn star_search_ui(
+    ui: &mut egui::Ui,
+    search: &mut StarSearch,
+    masterok: &Masterik,
+    star_data: &StarData,
+    explored: Option<&ExploredStars>,
+) -> Option<usize> {
+    let mut chosen = None;
+
+    egui::CollapsingHeader::new("Search")
+        .id_source("star_search")
+        .show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut search.query)
+                    .hint_text("name, #index, class:OB, radius>10000")
+                    .desired_width(f32::INFINITY),
+            );
+            search.refresh(masterok, star_data, explored);
+
+            if let Some(err) = &search.error {
+                ui.colored_label(egui::Color32::RED, err);
+                return;
+            }
+            if search.query.trim().is_empty() {
+                return;
+            }
+
+            ui.small(if search.total_matches > search.results.len() {
+                format!(
+                    "{} matches, showing the first {}",
+                    search.total_matches,
+                    search.results.len()
+                )
+            } else {
+                format!("{} matches", search.total_matches)
+            });
+
+            egui::ScrollArea::vertical()
+                .id_source("star_search_results")
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for &index in &search.results {
+                        let Some(info) = StarInfo::new(index, masterok, star_data) else {
+                            continue;
+                        };
+                        let label = format!("{} ({}-Class)", info.name, info.class.letter());
+                        if ui.selectable_label(false, label).clicked() {
+                            chosen = Some(index);
+                        }
+                    }
+                });
+        });
+
+    chosen
+}
+
+pub fn fly_to_star_system(
+    mut ev_fly: EventReader<FlyToStar>,
+    masterok: Res<Masterik>,
+    limits: Res<CameraLimits>,
+    explored: Option<Res<ExploredStars>>,
+    mut selected: ResMut<SelectedStar>,
+    mut ev_selected: EventWriter<StarSelected>,
+    mut query_camera: Query<
+        (&OrthographicProjection, &Transform, &mut CameraController),
+        With<GalaxyCamera>,
+    >,
+) {
+    let Some(FlyToStar(index)) = ev_fly.read().last() else {
+        return;
+    };
+    let Some(&(x, y, radius)) = masterok.positions.get(*index) else {
+        return;
+    };
+    if explored.is_some_and(|explored| !explored.reveals(*index)) {
+        return;
+    }
+
+    if selected.index != Some(*index) {
+        selected.index = Some(*index);
+        ev_selected.send(StarSelected(Some(*index)));
+    }
+
+    let Ok((projection, transform, mut controller)) = query_camera.get_single_mut() else {
+        return;
+    };
+    let scale = (radius * 2.0 / FLY_TO_STAR_PIXELS).clamp(limits.min_scale, limits.max_scale);
+    controller.fly_to(
+        transform.translation.truncate(),
+        projection.scale,
+        Vec2::new(x, y),
+        scale,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_part() {
+        let filter = StarFilter::parse("Vela class:ob radius>10000 radius<20000.5 #42").unwrap();
+        assert_eq!(filter.text, vec!["vela".to_string()]);
+        assert_eq!(filter.classes, vec![StarClass::O, StarClass::B]);
+        assert_eq!(filter.min_radius, Some(10000.0));
+        assert_eq!(filter.max_radius, Some(20000.5));
+        assert_eq!(filter.index, Some(42));
+    }
+
+    #[test]
+    fn blank_query_is_empty() {
+        assert!(StarFilter::parse("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_bad_tokens() {
+        assert!(StarFilter::parse("class:X").is_err());
+        assert!(StarFilter::parse("radius=5").is_err());
+        assert!(StarFilter::parse("radius>big").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::egui;
+
+use crate::camera::{GalaxyCamera, PointerDrag};
+use crate::exploration::ExploredStars;
+use crate::galaxy::star_color_from_radius;
+use crate::naming::{designation, star_name};
+use crate::resources::{GalaxyGenerated, Masterik, StarClass, StarData, StarSelected, UiFocus};
+use crate::spatial::StarGrid;
+
+//cursor travel in pixels below which a press and release counts as a click and not a drag
+const CLICK_TOLERANCE: f32 = 5.0;
+//how far from a star a click may land, in screen pixels
+pub const PICK_RADIUS_PIXELS: f32 = 24.0;
+
+//index into Masterik.positions of the star the player clicked
+#[derive(Resource, Default)]
+pub struct SelectedStar {
+    pub index: Option<usize>,
+}
+
+//everything the info card shows about a star
+pub struct StarInfo {
+    pub index: usize,
+    pub name: String,
+    pub designation: String,
+    pub class: StarClass,
+    pub radius: f32,
+    pub position: Vec2,
+    pub color: Color,
+    pub distance_from_core: f32,
+}
+
+impl StarInfo {
+    pub fn new(index: usize, masterok: &Masterik, star_data: &StarData) -> Option<Self> {
+        let &(x, y, radius) = masterok.positions.get(index)?;
+        let position = Vec2::new(x, y);
+        let class = StarClass::from_radius(radius, star_data);
+
+        Some(Self {
+            index,
+            name: star_name(masterok.gen_seed, index, class, position),
+            designation: designation(index, position),
+            class,
+            radius,
+            position,
+            color: star_color_from_radius(&(radius + 10.0), star_data),
+            distance_from_core: position.length(),
+        })
+    }
+}
+
+//cursor position in world space through the galaxy camera
+pub fn cursor_world_position(
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<GalaxyCamera>>,
+) -> Option<(Vec2, f32)> {
+    let cursor = window_query.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform, projection) = camera_query.get_single().ok()?;
+    let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+    Some((world, projection.scale))
+}
+
+//selects on release so dragging the camera around does not change the selection
+pub fn pick_star_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    drag: Res<PointerDrag>,
+    focus: Res<UiFocus>,
+    masterok: Res<Masterik>,
+    grid: Res<StarGrid>,
+    explored: Option<Res<ExploredStars>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<GalaxyCamera>>,
+    mut selected: ResMut<SelectedStar>,
+    mut ev_selected: EventWriter<StarSelected>,
+) {
+    if !mouse_buttons.just_released(MouseButton::Left)
+        || drag.moved > CLICK_TOLERANCE
+        || focus.pointer_over_ui
+        || masterok.block_input
+    {
+        return;
+    }
+
+    let Some((world, scale)) = cursor_world_position(&window_query, &camera_query) else {
+        return;
+    };
+
+    //stars in the fog can't be picked, the closest revealed one can
+    let picked = grid
+        .nearest_where(
+            &masterok.positions,
+            world,
+            PICK_RADIUS_PIXELS * scale,
+            |index| {
+                explored
+                    .as_ref()
+                    .map_or(true, |explored| explored.reveals(index))
+            },
+        )
+        .map(|(index, _)| index);
+    if picked != selected.index {
+        selected.index = picked;
+        ev_selected.send(StarSelected(picked));
+    }
+}
+
+//indices point into the old positions after a regeneration, so the selection goes away
+pub fn clear_selection_on_regenerate(
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut selected: ResMut<SelectedStar>,
+    mut ev_selected: EventWriter<StarSelected>,
+) {
+    if ev_generated.read().count() > 0 && selected.index.is_some() {
+        selected.index = None;
+        ev_selected.send(StarSelected(None));
+    }
+}
+
+//ring around the selected star that stays visible at any zoom
+pub fn highlight_selected_star(
+    selected: Res<SelectedStar>,
+    masterok: Res<Masterik>,
+    camera_query: Query<&OrthographicProjection, With<GalaxyCamera>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(&(x, y, radius)) = selected
+        .index
+        .and_then(|index| masterok.positions.get(index))
+    else {
+        return;
+    };
+    let scale = camera_query.get_single().map(|p| p.scale).unwrap_or(1.0);
+
+    let ring = (radius * 1.6).max(12.0 * scale);
+    gizmos
+        .circle_2d(Vec2::new(x, y), ring, Color::rgb(0.2, 1.0, 0.4))
+        .segments(48);
+}
+
+pub fn selected_star_ui(ui: &mut egui::Ui, info: &StarInfo) {
+    egui::CollapsingHeader::new("Selected Star")
+        .id_source("selected_star")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("selected_star_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Name");
+                    ui.strong(&info.name);
+                    ui.end_row();
+
+                    ui.label("Catalog");
+                    ui.label(&info.designation);
+                    ui.end_row();
+
+                    ui.label("Index");
+                    ui.label(info.index.to_string());
+                    ui.end_row();
+
+                    ui.label("Class");
+                    ui.label(format!(
+                        "{}-Class ({})",
+                        info.class.letter(),
+                        info.class.description()
+                    ));
+                    ui.end_row();
+
+                    ui.label("Radius");
+                    ui.label(format!("{:.0}", info.radius));
+                    ui.end_row();
+
+                    ui.label("Position");
+                    ui.label(format!("{:.0}, {:.0}", info.position.x, info.position.y));
+                    ui.end_row();
+
+                    ui.label("Color");
+                    let [r, g, b, _] = info.color.as_rgba_u8();
+                    let (rect, _) =
+                        ui.allocate_exact_size(egui::vec2(40.0, 14.0), egui::Sense::hover());
+                    ui.painter()
+                        .rect_filled(rect, 2.0, egui::Color32::from_rgb(r, g, b));
+                    ui.end_row();
+
+                    ui.label("From Core");
+                    ui.label(format!("{:.0}", info.distance_from_core));
+                    ui.end_row();
+                });
+        });
+}
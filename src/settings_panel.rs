@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::resources::{
+    ChangeSeed, GalaxySettings, Masterik, StarData, CLASS_RADIUS_GAP, MAX_CLASS_RADIUS, MAX_RARITY,
+    MAX_SPIRAL_ARMS, MAX_STARS, MIN_CLASS_RADIUS, MIN_STARS,
+};
+
+//editable copy of the settings, nothing touches the live galaxy until apply is pressed
+#[derive(Resource)]
+pub struct SettingsDraft {
+    pub settings: GalaxySettings,
+    pub star_data: StarData,
+    pub camera_move_speed: f32,
+    pub seed_text: String,
+    //live values the draft was taken from, used to tell user edits apart from keyboard changes
+    base_settings: GalaxySettings,
+    base_star_data: StarData,
+    base_camera_move_speed: f32,
+}
+
+impl FromWorld for SettingsDraft {
+    fn from_world(world: &mut World) -> Self {
+        let settings = world.resource::<Masterik>().settings();
+        let camera_move_speed = world.resource::<Masterik>().camera_move_speed;
+        let star_data = world.resource::<StarData>().clone();
+
+        Self {
+            seed_text: settings.gen_seed.to_string(),
+            base_settings: settings.clone(),
+            base_star_data: star_data.clone(),
+            base_camera_move_speed: camera_move_speed,
+            settings,
+            star_data,
+            camera_move_speed,
+        }
+    }
+}
+
+impl SettingsDraft {
+    pub fn revert(&mut self) {
+        self.settings = self.base_settings.clone();
+        self.star_data = self.base_star_data.clone();
+        self.camera_move_speed = self.base_camera_move_speed;
+        self.seed_text = self.settings.gen_seed.to_string();
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.settings != self.base_settings
+            || self.star_data != self.base_star_data
+            || self.camera_move_speed != self.base_camera_move_speed
+            || self.seed_text != self.settings.gen_seed.to_string()
+    }
+
+    fn parsed_seed(&self) -> Option<u64> {
+        self.seed_text.trim().parse().ok()
+    }
+}
+
+pub enum SettingsPanelAction {
+    Apply,
+    Revert,
+}
+
+//follows the live galaxy while the draft has no pending edits
+pub fn sync_settings_draft(
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    mut draft: ResMut<SettingsDraft>,
+) {
+    let live = masterok.settings();
+    if masterok.block_input
+        || (live == draft.base_settings
+            && *star_data == draft.base_star_data
+            && masterok.camera_move_speed == draft.base_camera_move_speed)
+    {
+        return;
+    }
+
+    let untouched = !draft.is_dirty();
+    draft.base_settings = live;
+    draft.base_star_data = star_data.clone();
+    draft.base_camera_move_speed = masterok.camera_move_speed;
+    if untouched {
+        draft.revert();
+    }
+}
+
+//writes the draft into the live resources, regenerates only if something generation related changed
+pub fn apply_settings_draft(
+    draft: &mut SettingsDraft,
+    masterok: &mut Masterik,
+    star_data: &mut StarData,
+    ev_change_seed: &mut EventWriter<ChangeSeed>,
+) {
+    let Some(seed) = draft.parsed_seed() else {
+        return;
+    };
+    draft.settings.gen_seed = seed;
+
+    masterok.camera_move_speed = draft.camera_move_speed;
+    draft.base_camera_move_speed = draft.camera_move_speed;
+
+    if draft.settings != masterok.settings() || draft.star_data != *star_data {
+        *star_data = draft.star_data.clone();
+        masterok.apply_settings(&draft.settings);
+        ev_change_seed.send(ChangeSeed);
+    }
+
+    draft.base_settings = draft.settings.clone();
+    draft.base_star_data = draft.star_data.clone();
+}
+
+pub fn settings_panel_ui(
+    ui: &mut egui::Ui,
+    draft: &mut SettingsDraft,
+) -> Option<SettingsPanelAction> {
+    let mut action = None;
+
+    egui::CollapsingHeader::new("Settings")
+        .id_source("galaxy_settings")
+        .show(ui, |ui| {
+            egui::Grid::new("galaxy_settings_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Seed");
+                    let seed_valid = draft.parsed_seed().is_some();
+                    let seed_edit = egui::TextEdit::singleline(&mut draft.seed_text)
+                        .desired_width(140.0)
+                        .text_color(if seed_valid {
+                            egui::Color32::LIGHT_GRAY
+                        } else {
+                            egui::Color32::RED
+                        });
+                    ui.add(seed_edit);
+                    ui.end_row();
+
+                    ui.label("Stars");
+                    ui.add(egui::Slider::new(
+                        &mut draft.settings.total_stars,
+                        MIN_STARS..=MAX_STARS,
+                    ));
+                    ui.end_row();
+
+                    ui.label("Spiral Arms");
+                    ui.add(egui::Slider::new(
+                        &mut draft.settings.spiral_arm_count,
+                        1..=MAX_SPIRAL_ARMS,
+                    ));
+                    ui.end_row();
+
+                    ui.label("Angle Mod");
+                    ui.add(
+                        egui::Slider::new(&mut draft.settings.angle_mod, 0.0001..=0.005)
+                            .max_decimals(5),
+                    );
+                    ui.end_row();
+
+                    ui.label("Radius Mod");
+                    ui.add(egui::Slider::new(
+                        &mut draft.settings.radius_mod,
+                        310.0..=10000.0,
+                    ));
+                    ui.end_row();
+
+                    ui.label("Distance Mod");
+                    ui.add(egui::Slider::new(
+                        &mut draft.settings.distance_mod,
+                        11.0..=300.0,
+                    ));
+                    ui.end_row();
+
+                    ui.label("Camera Speed");
+                    ui.add(egui::Slider::new(&mut draft.camera_move_speed, 1.0..=50.0));
+                    ui.end_row();
+                });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.checkbox(&mut draft.settings.o_class, "O");
+                ui.checkbox(&mut draft.settings.b_class, "B");
+                ui.checkbox(&mut draft.settings.a_class, "A");
+                ui.checkbox(&mut draft.settings.f_class, "F");
+                ui.checkbox(&mut draft.settings.g_class, "G");
+                ui.checkbox(&mut draft.settings.k_class, "K");
+                ui.checkbox(&mut draft.settings.m_class, "M");
+            });
+
+            egui::CollapsingHeader::new("Star Data")
+                .id_source("galaxy_star_data")
+                .show(ui, |ui| {
+                    star_data_grid(ui, &mut draft.star_data);
+                });
+
+            ui.horizontal(|ui| {
+                let dirty = draft.is_dirty();
+                let can_apply = dirty && draft.parsed_seed().is_some();
+                if ui
+                    .add_enabled(can_apply, egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    action = Some(SettingsPanelAction::Apply);
+                }
+                if ui.add_enabled(dirty, egui::Button::new("Revert")).clicked() {
+                    action = Some(SettingsPanelAction::Revert);
+                }
+            });
+        });
+
+    action
+}
+
+fn star_data_grid(ui: &mut egui::Ui, star_data: &mut StarData) {
+    egui::Grid::new("galaxy_star_data_grid")
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.label("Class");
+            ui.label("Radius");
+            ui.label("Rarity");
+            ui.end_row();
+
+            //from_radius and the spawn roll walk the classes in order, so each bound stays between its
+            //neighbours, radii shrinking and rarity thresholds growing from O to M
+            let (radii, rarities) = (star_data.radii(), star_data.rarities());
+            let rows = [
+                (
+                    "O",
+                    &mut star_data.o_class_radius,
+                    &mut star_data.o_class_rarity,
+                ),
+                (
+                    "B",
+                    &mut star_data.b_class_radius,
+                    &mut star_data.b_class_rarity,
+                ),
+                (
+                    "A",
+                    &mut star_data.a_class_radius,
+                    &mut star_data.a_class_rarity,
+                ),
+                (
+                    "F",
+                    &mut star_data.f_class_radius,
+                    &mut star_data.f_class_rarity,
+                ),
+                (
+                    "G",
+                    &mut star_data.g_class_radius,
+                    &mut star_data.g_class_rarity,
+                ),
+                (
+                    "K",
+                    &mut star_data.k_class_radius,
+                    &mut star_data.k_class_rarity,
+                ),
+                (
+                    "M",
+                    &mut star_data.m_class_radius,
+                    &mut star_data.m_class_rarity,
+                ),
+            ];
+
+            for (index, (name, radius, rarity)) in rows.into_iter().enumerate() {
+                let max_radius = match index {
+                    0 => MAX_CLASS_RADIUS,
+                    _ => radii[index - 1] - CLASS_RADIUS_GAP,
+                };
+                let min_radius = radii
+                    .get(index + 1)
+                    .map_or(MIN_CLASS_RADIUS, |smaller| smaller + CLASS_RADIUS_GAP);
+                let min_rarity = match index {
+                    0 => 0,
+                    _ => rarities[index - 1],
+                };
+                let max_rarity = rarities.get(index + 1).copied().unwrap_or(MAX_RARITY);
+
+                ui.label(name);
+                ui.add(
+                    egui::DragValue::new(radius)
+                        .speed(10.0)
+                        .clamp_range(min_radius..=max_radius.max(min_radius)),
+                );
+                ui.add(
+                    egui::DragValue::new(rarity)
+                        .speed(100.0)
+                        .clamp_range(min_rarity..=max_rarity.max(min_rarity)),
+                );
+                ui.end_row();
+            }
+        });
+}
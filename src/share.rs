@@ -0,0 +1,197 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::bookmarks::{Bookmark, IncomingBookmarks};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::resources::{ChangeSeed, GalaxySettings, Masterik, StarData};
+
+//bump when the fields change, codes from other versions are refused instead of misread
+pub const SHARE_VERSION: u32 = 1;
+
+//a galaxy and its bookmarks packed into one line of text that can be pasted anywhere
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareCode {
+    pub version: u32,
+    pub settings: GalaxySettings,
+    pub star_data: StarData,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+//read first to find out whether the rest of the code can be understood
+#[derive(Deserialize)]
+struct ShareHeader {
+    version: u32,
+}
+
+impl ShareCode {
+    pub fn new(settings: GalaxySettings, star_data: StarData, bookmarks: Vec<Bookmark>) -> Self {
+        Self {
+            version: SHARE_VERSION,
+            settings,
+            star_data,
+            bookmarks,
+        }
+    }
+
+    //compact ron in url safe base64, so codes also survive being put in a link
+    pub fn encode(&self) -> Result<String, GalaxyError> {
+        let text = ron::to_string(self).map_err(|err| GalaxyError::ShareCode(err.to_string()))?;
+        Ok(URL_SAFE_NO_PAD.encode(text))
+    }
+
+    pub fn decode(code: &str) -> Result<Self, GalaxyError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|err| GalaxyError::ShareCode(err.to_string()))?;
+        let text =
+            String::from_utf8(bytes).map_err(|err| GalaxyError::ShareCode(err.to_string()))?;
+
+        let header: ShareHeader =
+            ron::from_str(&text).map_err(|err| GalaxyError::ShareCode(err.to_string()))?;
+        if header.version != SHARE_VERSION {
+            return Err(GalaxyError::ShareCode(format!(
+                "made by another version (format {})",
+                header.version
+            )));
+        }
+        let shared: Self =
+            ron::from_str(&text).map_err(|err| GalaxyError::ShareCode(err.to_string()))?;
+
+        //codes are typed and pasted by hand, a tampered one must not reach the generator
+        shared
+            .settings
+            .validate()
+            .and_then(|()| shared.star_data.validate())
+            .map_err(GalaxyError::ShareCode)?;
+        Ok(shared)
+    }
+}
+
+//the text fields of the share section
+#[derive(Resource, Default)]
+pub struct ShareCodes {
+    pub input: String,
+    pub last_code: Option<String>,
+}
+
+#[derive(Event)]
+pub struct OpenShareCode(pub String);
+
+//regenerates the shared galaxy, its bookmarks get merged in by load_bookmarks_on_regenerate
+pub fn open_share_code_system(
+    mut ev_open: EventReader<OpenShareCode>,
+    mut masterok: ResMut<Masterik>,
+    mut star_data: ResMut<StarData>,
+    mut incoming: ResMut<IncomingBookmarks>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    let Some(OpenShareCode(code)) = ev_open.read().last() else {
+        return;
+    };
+    if masterok.block_input {
+        return;
+    }
+
+    match ShareCode::decode(code) {
+        Ok(shared) => {
+            masterok.apply_settings(&shared.settings);
+            star_data.set_if_neq(shared.star_data);
+            *incoming = IncomingBookmarks {
+                settings: Some(shared.settings),
+                list: shared.bookmarks,
+            };
+            ev_change_seed.send(ChangeSeed);
+        }
+        Err(err) => toasts.report(&err),
+    }
+}
+
+pub enum ShareAction {
+    Copy,
+    Open,
+}
+
+pub fn share_ui(ui: &mut egui::Ui, share: &mut ShareCodes) -> Option<ShareAction> {
+    let mut action = None;
+    egui::CollapsingHeader::new("Share")
+        .id_source("share_codes")
+        .show(ui, |ui| {
+            if ui.button("Copy Share Code").clicked() {
+                action = Some(ShareAction::Copy);
+            }
+            //clipboards don't always work on the web, so the code can be selected by hand too
+            if let Some(code) = &share.last_code {
+                ui.add(egui::TextEdit::singleline(&mut code.as_str()).desired_width(f32::INFINITY));
+            }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut share.input)
+                        .hint_text("Paste a code")
+                        .desired_width(180.0),
+                );
+                if ui
+                    .add_enabled(!share.input.trim().is_empty(), egui::Button::new("Open"))
+                    .clicked()
+                {
+                    action = Some(ShareAction::Open);
+                }
+            });
+            ui.small("Codes hold the galaxy settings, star classes and bookmarks");
+        });
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared() -> ShareCode {
+        ShareCode::new(
+            GalaxySettings {
+                total_stars: 45000,
+                gen_seed: 987654321,
+                spiral_arm_count: 3,
+                o_class: true,
+                b_class: false,
+                a_class: true,
+                f_class: true,
+                g_class: true,
+                k_class: false,
+                m_class: true,
+                angle_mod: 0.0009,
+                radius_mod: 3000.0,
+                distance_mod: 80.0,
+            },
+            StarData::default(),
+            vec![Bookmark {
+                name: "Outer Rim".into(),
+                translation: Vec2::new(-52000.0, 18000.0),
+                scale: 12.5,
+            }],
+        )
+    }
+
+    #[test]
+    fn round_trips() {
+        let code = shared().encode().unwrap();
+        let decoded = ShareCode::decode(&format!("  {}\n", code)).unwrap();
+        assert_eq!(decoded.version, SHARE_VERSION);
+        assert_eq!(decoded.settings, shared().settings);
+        assert_eq!(decoded.star_data, shared().star_data);
+        assert_eq!(decoded.bookmarks, shared().bookmarks);
+    }
+
+    #[test]
+    fn refuses_other_versions_and_garbage() {
+        let mut other = shared();
+        other.version = SHARE_VERSION + 1;
+        let code = other.encode().unwrap();
+        assert!(ShareCode::decode(&code).is_err());
+        assert!(ShareCode::decode("not a share code").is_err());
+    }
+}
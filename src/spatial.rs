@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::camera::GalaxyCamera;
+use crate::exploration::ExploredStars;
+use crate::resources::{GalaxyGenerated, Masterik, UiFocus};
+use crate::selection::{cursor_world_position, PICK_RADIUS_PIXELS};
+use crate::system_view::ViewMode;
+
+//roughly this many stars end up in one cell on average
+const STARS_PER_CELL: f32 = 4.0;
+//keeps cells from collapsing when only a handful of stars exist
+const MIN_CELL_SIZE: f32 = 1000.0;
+
+//uniform grid over Masterik.positions so nearest star lookups don't scan every star
+#[derive(Resource, Default)]
+pub struct StarGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    min_cell: (i32, i32),
+    max_cell: (i32, i32),
+    //biggest star radius, distances are measured to star edges so the search has to reach this far extra
+    max_radius: f32,
+}
+
+impl StarGrid {
+    pub fn build(positions: &[(f32, f32, f32)]) -> Self {
+        let mut grid = Self::default();
+        if positions.is_empty() {
+            return grid;
+        }
+
+        let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+        for &(x, y, radius) in positions {
+            min = min.min(Vec2::new(x, y));
+            max = max.max(Vec2::new(x, y));
+            grid.max_radius = grid.max_radius.max(radius);
+        }
+
+        let area = ((max.x - min.x) * (max.y - min.y)).max(1.0);
+        grid.cell_size = (area * STARS_PER_CELL / positions.len() as f32)
+            .sqrt()
+            .max(MIN_CELL_SIZE);
+        grid.min_cell = grid.cell_of(min);
+        grid.max_cell = grid.cell_of(max);
+
+        for (index, &(x, y, _)) in positions.iter().enumerate() {
+            let cell = grid.cell_of(Vec2::new(x, y));
+            grid.cells.entry(cell).or_default().push(index);
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    //closest star edge to a world position, None if nothing is within max_distance
+    pub fn nearest(
+        &self,
+        positions: &[(f32, f32, f32)],
+        point: Vec2,
+        max_distance: f32,
+    ) -> Option<(usize, f32)> {
+        self.nearest_where(positions, point, max_distance, |_| true)
+    }
+
+    //same as nearest but only stars accepted by the predicate count, so a rejected star never hides one behind it
+    pub fn nearest_where(
+        &self,
+        positions: &[(f32, f32, f32)],
+        point: Vec2,
+        max_distance: f32,
+        accept: impl Fn(usize) -> bool,
+    ) -> Option<(usize, f32)> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        let (cx, cy) = self.cell_of(point);
+        //past this ring every cell of the grid has been visited
+        let last_ring = [
+            cx - self.min_cell.0,
+            self.max_cell.0 - cx,
+            cy - self.min_cell.1,
+            self.max_cell.1 - cy,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+        .max(0);
+
+        let mut best: Option<(usize, f32)> = None;
+        for ring in 0..=last_ring {
+            //anything in this ring or further out is at least this far away
+            let closest_possible = (ring - 1).max(0) as f32 * self.cell_size - self.max_radius;
+            let limit = best.map_or(max_distance, |(_, distance)| distance.min(max_distance));
+            if closest_possible > limit {
+                break;
+            }
+
+            for x in cx - ring..=cx + ring {
+                for y in cy - ring..=cy + ring {
+                    if (x - cx).abs() != ring && (y - cy).abs() != ring {
+                        continue;
+                    }
+                    let Some(indices) = self.cells.get(&(x, y)) else {
+                        continue;
+                    };
+                    for &index in indices {
+                        let Some(&(sx, sy, radius)) = positions.get(index) else {
+                            continue;
+                        };
+                        let distance = Vec2::new(sx, sy).distance(point) - radius;
+                        if distance <= max_distance
+                            && best.map_or(true, |(_, best_distance)| distance < best_distance)
+                            && accept(index)
+                        {
+                            best = Some((index, distance));
+                        }
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    //every star whose center is within radius of point
+    pub fn within(&self, positions: &[(f32, f32, f32)], point: Vec2, radius: f32) -> Vec<usize> {
+        if self.cells.is_empty() {
+            return Vec::new();
+        }
+
+        let (min_x, min_y) = self.cell_of(point - Vec2::splat(radius));
+        let (max_x, max_y) = self.cell_of(point + Vec2::splat(radius));
+        let mut found = Vec::new();
+        for x in min_x.max(self.min_cell.0)..=max_x.min(self.max_cell.0) {
+            for y in min_y.max(self.min_cell.1)..=max_y.min(self.max_cell.1) {
+                let Some(indices) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+                found.extend(indices.iter().copied().filter(|&index| {
+                    positions.get(index).is_some_and(|&(sx, sy, _)| {
+                        Vec2::new(sx, sy).distance_squared(point) <= radius * radius
+                    })
+                }));
+            }
+        }
+        found
+    }
+}
+
+//what is under the cursor this frame, world is None when the cursor is off the map, over the panel or in a system
+#[derive(Resource, Default)]
+pub struct HoverInfo {
+    pub world: Option<Vec2>,
+    pub nearest: Option<(usize, f32)>,
+}
+
+//stars only change through generation, adding and removing, all of which change the count or send GalaxyGenerated
+pub fn rebuild_star_grid(
+    masterok: Res<Masterik>,
+    mut ev_generated: EventReader<GalaxyGenerated>,
+    mut grid: ResMut<StarGrid>,
+    mut last_len: Local<usize>,
+) {
+    let regenerated = ev_generated.read().count() > 0;
+    if masterok.block_input || (!regenerated && masterok.positions.len() == *last_len) {
+        return;
+    }
+    *last_len = masterok.positions.len();
+    *grid = StarGrid::build(&masterok.positions);
+}
+
+pub fn update_hover_info(
+    focus: Res<UiFocus>,
+    masterok: Res<Masterik>,
+    grid: Res<StarGrid>,
+    explored: Option<Res<ExploredStars>>,
+    view: Res<State<ViewMode>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<GalaxyCamera>>,
+    mut hover: ResMut<HoverInfo>,
+) {
+    let cursor = cursor_world_position(&window_query, &camera_query).filter(|_| {
+        !focus.pointer_over_ui && !masterok.block_input && *view.get() == ViewMode::Galaxy
+    });
+
+    hover.world = cursor.map(|(world, _)| world);
+    //no peeking into the fog, and only stars close enough on screen that a click would pick them
+    hover.nearest = cursor.and_then(|(world, scale)| {
+        grid.nearest_where(
+            &masterok.positions,
+            world,
+            PICK_RADIUS_PIXELS * scale,
+            |star| {
+                explored
+                    .as_ref()
+                    .map_or(true, |explored| explored.reveals(star))
+            },
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITIONS: [(f32, f32, f32); 4] = [
+        (0.0, 0.0, 10.0),
+        (5000.0, 0.0, 10.0),
+        (0.0, 8000.0, 100.0),
+        (-30000.0, -30000.0, 10.0),
+    ];
+
+    #[test]
+    fn nearest_measures_to_star_edges() {
+        let grid = StarGrid::build(&POSITIONS);
+        assert_eq!(
+            grid.nearest(&POSITIONS, Vec2::new(4000.0, 0.0), f32::MAX),
+            Some((1, 990.0))
+        );
+        assert_eq!(
+            grid.nearest(&POSITIONS, Vec2::new(-29000.0, -30000.0), f32::MAX),
+            Some((3, 990.0))
+        );
+        assert_eq!(
+            grid.nearest(&POSITIONS, Vec2::new(4000.0, 0.0), 500.0),
+            None
+        );
+    }
+
+    #[test]
+    fn nearest_where_skips_rejected_stars() {
+        let grid = StarGrid::build(&POSITIONS);
+        assert_eq!(
+            grid.nearest_where(&POSITIONS, Vec2::new(4000.0, 0.0), f32::MAX, |star| {
+                star != 1
+            }),
+            Some((0, 3990.0))
+        );
+        assert_eq!(
+            grid.nearest_where(&POSITIONS, Vec2::ZERO, f32::MAX, |_| false),
+            None
+        );
+    }
+
+    #[test]
+    fn within_finds_star_centers_in_range() {
+        let grid = StarGrid::build(&POSITIONS);
+        let mut found = grid.within(&POSITIONS, Vec2::ZERO, 6000.0);
+        found.sort();
+        assert_eq!(found, vec![0, 1]);
+        assert!(grid
+            .within(&POSITIONS, Vec2::new(20000.0, 20000.0), 100.0)
+            .is_empty());
+        assert!(StarGrid::default()
+            .within(&POSITIONS, Vec2::ZERO, 6000.0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+//tiny key/value text storage: files in the config dir natively, localStorage on the web
+
+use std::io;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn storage_path(key: &str) -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("kosmos")
+        .join(format!("{}.ron", key))
+}
+
+//returns Ok(None) when nothing has been stored under the key yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_text(key: &str) -> io::Result<Option<String>> {
+    match std::fs::read_to_string(storage_path(key)) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_text(key: &str, text: &str) -> io::Result<()> {
+    let path = storage_path(key);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, text)
+}
+
+//exports are meant to be opened by other tools, so they go next to the user's documents instead of the config
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_export(file_name: &str, text: &str) -> io::Result<std::path::PathBuf> {
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("kosmos");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+//the browser has no documents folder, the export is handed to it as a download instead
+#[cfg(target_arch = "wasm32")]
+pub fn write_export(file_name: &str, text: &str) -> io::Result<std::path::PathBuf> {
+    use wasm_bindgen::JsCast;
+
+    let failed =
+        |what: &str| io::Error::new(io::ErrorKind::Other, format!("download failed: {}", what));
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(text));
+    let blob = web_sys::Blob::new_with_str_sequence(&parts).map_err(|_| failed("blob"))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|_| failed("url"))?;
+
+    let anchor = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("a").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlAnchorElement>().ok())
+        .ok_or_else(|| failed("no document"))?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    //the url is not revoked, some browsers cancel the download when it goes away right after the click
+    anchor.click();
+    Ok(std::path::PathBuf::from(file_name))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "localStorage unavailable"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_text(key: &str) -> io::Result<Option<String>> {
+    local_storage()?
+        .get_item(&format!("kosmos.{}", key))
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "localStorage read failed"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write_text(key: &str, text: &str) -> io::Result<()> {
+    local_storage()?
+        .set_item(&format!("kosmos.{}", key), text)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "localStorage write failed"))
+}
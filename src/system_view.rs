@@ -0,0 +1,305 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::camera::{CameraController, GalaxyCamera};
+use crate::galaxy::{star_color_from_radius, star_texture};
+use crate::input::{Action, ActionState, KeyMap};
+use crate::planets::{PlanetKind, StarSystem, SystemCache};
+use crate::resources::{
+    ChangeSeed, Masterik, StarClass, StarCount, StarData, StarsAdded, StarsRemoved,
+};
+use crate::selection::SelectedStar;
+
+//outermost orbit is drawn at this distance from the star, in world units
+const VIEW_RADIUS: f32 = 20000.0;
+//camera zoom that fits the whole system on screen
+const VIEW_SCALE: f32 = 60.0;
+//how long a one year orbit takes on screen
+const SECONDS_PER_YEAR: f32 = 5.0;
+
+//which of the two scenes is being looked at
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ViewMode {
+    #[default]
+    Galaxy,
+    System,
+}
+
+//galaxy camera as it was before opening a system, put back when leaving
+#[derive(Resource)]
+pub struct SavedGalaxyView {
+    pub translation: Vec3,
+    pub scale: f32,
+    pub target_scale: f32,
+}
+
+//everything spawned for the system view, despawned together when leaving it
+#[derive(Component)]
+pub struct SystemViewEntity;
+
+#[derive(Component)]
+pub struct OrbitingBody {
+    pub distance: f32,
+    //seconds for one full orbit on screen
+    pub seconds_per_orbit: f32,
+    pub phase: f32,
+}
+
+//orbits are spread by square root so the inner planets don't all sit on top of the star
+fn view_distance(orbit: f32, outermost: f32) -> f32 {
+    (orbit / outermost).sqrt() * VIEW_RADIUS
+}
+
+fn planet_color(kind: PlanetKind, habitable: bool) -> Color {
+    match (kind, habitable) {
+        (_, true) => Color::rgb_u8(60, 200, 90),
+        (PlanetKind::Rocky, false) => Color::rgb_u8(170, 120, 90),
+        (PlanetKind::GasGiant, false) => Color::rgb_u8(220, 170, 110),
+        (PlanetKind::Ice, false) => Color::rgb_u8(150, 200, 240),
+    }
+}
+
+fn outermost_orbit(system: &StarSystem) -> f32 {
+    system
+        .planets
+        .iter()
+        .map(|planet| planet.orbit)
+        .chain(system.belts.iter().map(|belt| belt.orbit))
+        .chain(std::iter::once(system.habitable_zone.1))
+        .fold(f32::EPSILON, f32::max)
+}
+
+//enter opens the selected star, escape goes back
+pub fn system_view_input(
+    actions: Res<ActionState>,
+    selected: Res<SelectedStar>,
+    view: Res<State<ViewMode>>,
+    mut next_view: ResMut<NextState<ViewMode>>,
+) {
+    match view.get() {
+        ViewMode::Galaxy
+            if actions.just_pressed(Action::EnterSystem) && selected.index.is_some() =>
+        {
+            next_view.set(ViewMode::System)
+        }
+        ViewMode::System if actions.just_pressed(Action::LeaveSystem) => {
+            next_view.set(ViewMode::Galaxy)
+        }
+        _ => (),
+    }
+}
+
+pub fn enter_system_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    selected: Res<SelectedStar>,
+    mut cache: ResMut<SystemCache>,
+    mut next_view: ResMut<NextState<ViewMode>>,
+    mut stars: Query<&mut Visibility, With<StarCount>>,
+    mut query_camera: Query<
+        (
+            &mut OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    let star = selected.index.and_then(|index| {
+        masterok
+            .positions
+            .get(index)
+            .map(|&(_, _, radius)| (index, radius))
+    });
+    let (Some((index, radius)), Ok((mut projection, mut transform, mut controller))) =
+        (star, query_camera.get_single_mut())
+    else {
+        next_view.set(ViewMode::Galaxy);
+        return;
+    };
+
+    commands.insert_resource(SavedGalaxyView {
+        translation: transform.translation,
+        scale: projection.scale,
+        target_scale: controller.target_scale,
+    });
+    transform.translation.x = 0.0;
+    transform.translation.y = 0.0;
+    projection.scale = VIEW_SCALE;
+    controller.target_scale = VIEW_SCALE;
+    controller.flight = None;
+
+    for mut visibility in stars.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+
+    let class = StarClass::from_radius(radius, &star_data);
+    let system = cache.get_or_generate(masterok.gen_seed, index, class);
+    let outermost = outermost_orbit(system);
+    let texture = star_texture(&asset_server);
+
+    commands.spawn((
+        SpriteBundle {
+            texture: texture.clone(),
+            sprite: Sprite {
+                color: star_color_from_radius(&(radius + 10.0), &star_data),
+                custom_size: Some(Vec2::splat(VIEW_RADIUS * 0.08)),
+                ..default()
+            },
+            ..default()
+        },
+        SystemViewEntity,
+    ));
+
+    for planet in &system.planets {
+        commands.spawn((
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite {
+                    color: planet_color(planet.kind, planet.habitable),
+                    //sizes are squashed as well, a gas giant would dwarf a rocky planet otherwise
+                    custom_size: Some(Vec2::splat(planet.radius.sqrt() * VIEW_RADIUS * 0.012)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..default()
+            },
+            OrbitingBody {
+                distance: view_distance(planet.orbit, outermost),
+                seconds_per_orbit: (planet.period * SECONDS_PER_YEAR).clamp(2.0, 600.0),
+                phase: planet.phase,
+            },
+            SystemViewEntity,
+        ));
+    }
+}
+
+pub fn exit_system_view(
+    mut commands: Commands,
+    saved: Option<Res<SavedGalaxyView>>,
+    spawned: Query<Entity, With<SystemViewEntity>>,
+    mut stars: Query<&mut Visibility, With<StarCount>>,
+    mut query_camera: Query<
+        (
+            &mut OrthographicProjection,
+            &mut Transform,
+            &mut CameraController,
+        ),
+        With<GalaxyCamera>,
+    >,
+) {
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+    for mut visibility in stars.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+
+    let (Some(saved), Ok((mut projection, mut transform, mut controller))) =
+        (saved, query_camera.get_single_mut())
+    else {
+        return;
+    };
+    transform.translation = saved.translation;
+    projection.scale = saved.scale;
+    controller.target_scale = saved.target_scale;
+    controller.flight = None;
+    commands.remove_resource::<SavedGalaxyView>();
+}
+
+pub fn animate_orbits(time: Res<Time>, mut bodies: Query<(&OrbitingBody, &mut Transform)>) {
+    let elapsed = time.elapsed_seconds();
+    for (body, mut transform) in bodies.iter_mut() {
+        let angle = body.phase + elapsed / body.seconds_per_orbit * std::f32::consts::TAU;
+        transform.translation.x = angle.cos() * body.distance;
+        transform.translation.y = angle.sin() * body.distance;
+    }
+}
+
+//orbit lines, belts and the habitable zone of the open system
+pub fn draw_system_orbits(
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    selected: Res<SelectedStar>,
+    cache: Res<SystemCache>,
+    mut gizmos: Gizmos,
+) {
+    let Some((index, &(_, _, radius))) = selected
+        .index
+        .and_then(|index| masterok.positions.get(index).map(|star| (index, star)))
+    else {
+        return;
+    };
+    let Some(system) = cache.get(index, StarClass::from_radius(radius, &star_data)) else {
+        return;
+    };
+    let outermost = outermost_orbit(system);
+
+    let (inner, outer) = system.habitable_zone;
+    for edge in [inner, outer] {
+        gizmos
+            .circle_2d(
+                Vec2::ZERO,
+                view_distance(edge, outermost),
+                Color::rgba(0.2, 0.8, 0.3, 0.35),
+            )
+            .segments(96);
+    }
+    for planet in &system.planets {
+        gizmos
+            .circle_2d(
+                Vec2::ZERO,
+                view_distance(planet.orbit, outermost),
+                Color::rgba(0.6, 0.6, 0.7, 0.4),
+            )
+            .segments(96);
+    }
+    for belt in &system.belts {
+        let distance = view_distance(belt.orbit, outermost);
+        for offset in [-0.015, 0.0, 0.015] {
+            gizmos
+                .circle_2d(
+                    Vec2::ZERO,
+                    distance * (1.0 + offset),
+                    Color::rgba(0.6, 0.5, 0.4, 0.5),
+                )
+                .segments(128);
+        }
+    }
+}
+
+//changing the galaxy under an open system closes it, the stars behind the indices are different now
+pub fn leave_system_view_on_change(
+    mut ev_change_seed: EventReader<ChangeSeed>,
+    mut ev_added: EventReader<StarsAdded>,
+    mut ev_removed: EventReader<StarsRemoved>,
+    mut next_view: ResMut<NextState<ViewMode>>,
+) {
+    let changed =
+        ev_change_seed.read().count() + ev_added.read().count() + ev_removed.read().count();
+    if changed > 0 {
+        next_view.set(ViewMode::Galaxy);
+    }
+}
+
+//button under the selected star card, returns the view to switch to
+pub fn system_view_button(ui: &mut egui::Ui, view: ViewMode, keymap: &KeyMap) -> Option<ViewMode> {
+    match view {
+        ViewMode::Galaxy => ui
+            .button(format!(
+                "View System [{}]",
+                keymap.label(Action::EnterSystem)
+            ))
+            .clicked()
+            .then_some(ViewMode::System),
+        ViewMode::System => ui
+            .button(format!(
+                "Back to Galaxy [{}]",
+                keymap.label(Action::LeaveSystem)
+            ))
+            .clicked()
+            .then_some(ViewMode::Galaxy),
+    }
+}
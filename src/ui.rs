@@ -0,0 +1,756 @@
+use bevy::diagnostic::DiagnosticsStore;
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext, EguiContexts};
+
+use egui_ratatui::RataguiBackend;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::{Line, Modifier, Stylize, Terminal},
+    text::Text,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::attract::{attract_settings_ui, AttractMode, AttractSettings};
+use crate::bookmarks::{
+    bookmarks_ui, AddBookmark, BookmarkAction, BookmarkDraft, Bookmarks, JumpToBookmark,
+    BOOKMARK_KEYS,
+};
+use crate::catalog::{catalog_export_ui, CatalogExport, ExportCatalog};
+use crate::cinematic::{camera_path_ui, AddKeyframe, CameraPath, Cinematic};
+use crate::economy::{star_resources_ui, StarEconomy};
+use crate::error::{ErrorToasts, GalaxyError};
+use crate::exploration::{exploration_ui, Exploration, ExploredStars};
+use crate::factions::{factions_ui, FactionSettings, Territories};
+use crate::history::{history_list_ui, SettingsHistory};
+use crate::input::{Action, ActionState, KeyMap};
+use crate::lanes::{lane_settings_ui, LaneSettings, StarLanes};
+use crate::legends::{
+    legends_ui, ExportLegends, Legends, LegendsAction, LegendsExport, LegendsView,
+};
+use crate::overlay::{overlay_mode_ui, OverlayMode};
+use crate::planets::{star_system_ui, SystemCache};
+use crate::preferences::{display_preferences_ui, Preferences};
+use crate::quit::QUIT_SUPPORTED;
+use crate::resources::{BevyTerminal, ChangeSeed, Masterik, StarClass, StarData, UiFocus};
+use crate::routes::{route_ui, PlannedRoute, RouteRequest};
+use crate::savegame::{save_slots_ui, LoadFromSlot, SaveSlots, SaveToSlot, SlotAction};
+use crate::search::{star_search_ui, FlyToStar, StarSearch};
+use crate::selection::{selected_star_ui, SelectedStar, StarInfo};
+use crate::settings_panel::{
+    apply_settings_draft, settings_panel_ui, SettingsDraft, SettingsPanelAction,
+};
+use crate::share::{share_ui, OpenShareCode, ShareAction, ShareCode, ShareCodes};
+use crate::spatial::HoverInfo;
+use crate::system_view::{system_view_button, ViewMode};
+
+//where a section sits in the panel, top to bottom
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PanelSlot {
+    SelectedStar,
+    SystemView,
+    Search,
+    Settings,
+    History,
+    Lanes,
+    Exploration,
+    Route,
+    Overlay,
+    Factions,
+    Legends,
+    Catalog,
+    CameraPath,
+    Bookmarks,
+    Share,
+    Saves,
+    Display,
+    Attract,
+}
+
+//every section is a system of its own, it gets a child ui of the panel and hands it back so the panel
+//knows how much room it took
+pub type PanelSectionId = SystemId<egui::Ui, egui::Ui>;
+
+#[derive(Resource, Default)]
+pub struct PanelSections {
+    sections: Vec<(PanelSlot, PanelSectionId)>,
+}
+
+//lets each plugin put its own sections into the panel, they only run when GalaxyUiPlugin is added
+pub trait AddPanelSection {
+    fn add_panel_section<M>(
+        &mut self,
+        slot: PanelSlot,
+        section: impl IntoSystem<egui::Ui, egui::Ui, M> + 'static,
+    ) -> &mut Self;
+}
+
+impl AddPanelSection for App {
+    fn add_panel_section<M>(
+        &mut self,
+        slot: PanelSlot,
+        section: impl IntoSystem<egui::Ui, egui::Ui, M> + 'static,
+    ) -> &mut Self {
+        let id = self.world.register_system(section);
+        let mut sections = self
+            .world
+            .get_resource_or_insert_with(PanelSections::default);
+        sections.sections.push((slot, id));
+        sections.sections.sort_by_key(|(slot, _)| *slot);
+        self
+    }
+}
+
+//whether the side panel is shown, the toggle key and the buttons flip it
+#[derive(Resource)]
+pub struct PanelState {
+    pub visible: bool,
+}
+
+impl Default for PanelState {
+    fn default() -> Self {
+        Self { visible: true }
+    }
+}
+
+pub fn toggle_panel_system(actions: Res<ActionState>, mut panel: ResMut<PanelState>) {
+    if actions.just_pressed(Action::TogglePanel) {
+        panel.visible = !panel.visible;
+    }
+}
+
+// Render to the terminal, the egui panel is drawn by side_panel_system
+pub fn draw_info_terminal(
+    mut termres: ResMut<BevyTerminal<RataguiBackend>>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    keymap: Res<KeyMap>,
+    hover: Res<HoverInfo>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut toasts: ResMut<ErrorToasts>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(60.0);
+
+    //draws info to ratatui terminal
+    if let Some(terminal) = termres.terminal_info.as_mut() {
+        if let Err(err) = draw_info_menu(terminal, &masterok, &star_data, &keymap, &hover, fps) {
+            toasts.report(&err);
+        }
+    }
+}
+
+//lays the registered sections out in the side panel, exclusive so every section can run as its own system
+pub fn side_panel_system(world: &mut World) {
+    //playback is for recording trailers and attract mode for unattended screens, nothing but the galaxy on screen
+    let playing = world
+        .get_resource::<Cinematic>()
+        .is_some_and(|cinematic| cinematic.playback.is_some())
+        || world
+            .get_resource::<AttractMode>()
+            .is_some_and(AttractMode::is_running);
+    if playing {
+        return;
+    }
+
+    let mut contexts = world.query_filtered::<&mut EguiContext, With<PrimaryWindow>>();
+    let Ok(mut context) = contexts.get_single_mut(world) else {
+        return;
+    };
+    let ctx = context.get_mut().clone();
+    let hide_label = format!(
+        "Hide Panel [{}]",
+        world.resource::<KeyMap>().label(Action::TogglePanel)
+    );
+
+    //hidden panel leaves only a button to bring it back
+    if !world.resource::<PanelState>().visible {
+        let show_label = hide_label.replacen("Hide", "Show", 1);
+        egui::Area::new(egui::Id::new("show_panel"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+            .show(&ctx, |ui| {
+                if ui.button(show_label).clicked() {
+                    world.resource_mut::<PanelState>().visible = true;
+                }
+            });
+        return;
+    }
+
+    let sections = world
+        .get_resource::<PanelSections>()
+        .map(|sections| sections.sections.clone())
+        .unwrap_or_default();
+
+    let frame = egui::Frame::default()
+        .inner_margin(1.0)
+        .outer_margin(1.0)
+        .fill(egui::Color32::BLACK);
+
+    //limit panel to certain size that is guaranteed to fit text
+    egui::SidePanel::right("my_left_panel")
+        .frame(frame)
+        .min_width(322.0)
+        .max_width(322.0)
+        .show(&ctx, |ui| {
+            //egui controls get laid out first at the bottom, the terminal fills whatever is left
+            egui::TopBottomPanel::bottom("galaxy_controls")
+                .frame(frame)
+                .show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("galaxy_controls_scroll")
+                        .max_height(ui.ctx().screen_rect().height() * 0.5)
+                        .show(ui, |ui| {
+                            if ui.small_button(hide_label).clicked() {
+                                world.resource_mut::<PanelState>().visible = false;
+                            }
+
+                            //same as ui.scope, with the section running in between
+                            for (slot, section) in sections {
+                                let child = ui.child_ui_with_id_source(
+                                    ui.available_rect_before_wrap(),
+                                    *ui.layout(),
+                                    slot,
+                                );
+                                match world.run_system_with_input(section, child) {
+                                    Ok(child) => {
+                                        ui.allocate_rect(child.min_rect(), egui::Sense::hover());
+                                    }
+                                    Err(err) => warn!("panel section {:?}: {:?}", slot, err),
+                                }
+                            }
+                        });
+                });
+
+            //without a terminal the panel still shows the egui controls
+            let seed = world.resource::<Masterik>().gen_seed;
+            match world
+                .resource_mut::<BevyTerminal<RataguiBackend>>()
+                .terminal_info
+                .as_mut()
+            {
+                Some(terminal) => {
+                    ui.add(terminal.backend_mut());
+                }
+                None => {
+                    ui.label(format!("Seed: {}", seed));
+                }
+            }
+        });
+}
+
+//the selected star with its resources and planets, when their plugins are added
+pub fn selected_star_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    selected: Res<SelectedStar>,
+    economy: Option<Res<StarEconomy>>,
+    systems: Option<Res<SystemCache>>,
+) -> egui::Ui {
+    let Some(info) = selected
+        .index
+        .and_then(|index| StarInfo::new(index, &masterok, &star_data))
+    else {
+        return ui;
+    };
+    selected_star_ui(&mut ui, &info);
+    if let Some(resources) = economy.as_ref().and_then(|economy| economy.get(info.index)) {
+        star_resources_ui(&mut ui, resources);
+    }
+    if let Some(system) = systems
+        .as_ref()
+        .and_then(|systems| systems.get(info.index, info.class))
+    {
+        star_system_ui(&mut ui, system);
+    }
+    ui
+}
+
+//sits right under the selected star card
+pub fn system_view_section(
+    In(mut ui): In<egui::Ui>,
+    selected: Res<SelectedStar>,
+    keymap: Res<KeyMap>,
+    view: Res<State<ViewMode>>,
+    mut next_view: ResMut<NextState<ViewMode>>,
+) -> egui::Ui {
+    if selected.index.is_none() {
+        return ui;
+    }
+    if let Some(view) = system_view_button(&mut ui, *view.get(), &keymap) {
+        next_view.set(view);
+    }
+    ui
+}
+
+pub fn search_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    explored: Option<Res<ExploredStars>>,
+    mut search: ResMut<StarSearch>,
+    mut ev_fly: EventWriter<FlyToStar>,
+) -> egui::Ui {
+    if let Some(index) = star_search_ui(
+        &mut ui,
+        &mut search,
+        &masterok,
+        &star_data,
+        explored.as_deref(),
+    ) {
+        ev_fly.send(FlyToStar(index));
+    }
+    ui
+}
+
+pub fn settings_section(
+    In(mut ui): In<egui::Ui>,
+    mut masterok: ResMut<Masterik>,
+    mut star_data: ResMut<StarData>,
+    mut draft: ResMut<SettingsDraft>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+) -> egui::Ui {
+    match settings_panel_ui(&mut ui, &mut draft) {
+        Some(SettingsPanelAction::Apply) if !masterok.block_input => {
+            apply_settings_draft(
+                &mut draft,
+                &mut masterok,
+                &mut star_data,
+                &mut ev_change_seed,
+            );
+        }
+        Some(SettingsPanelAction::Revert) => draft.revert(),
+        _ => (),
+    }
+    ui
+}
+
+//clicking an earlier entry regenerates that galaxy
+pub fn history_section(
+    In(mut ui): In<egui::Ui>,
+    mut masterok: ResMut<Masterik>,
+    mut history: ResMut<SettingsHistory>,
+    mut ev_change_seed: EventWriter<ChangeSeed>,
+) -> egui::Ui {
+    if let Some(index) = history_list_ui(&mut ui, &history) {
+        if !masterok.block_input && !history.is_replaying() && index != history.cursor {
+            if let Some(settings) = history.jump(index) {
+                masterok.apply_settings(&settings);
+                ev_change_seed.send(ChangeSeed);
+            }
+        }
+    }
+    ui
+}
+
+//edited on a copy, writing the resource every frame would rebuild the lanes every frame
+pub fn lanes_section(
+    In(mut ui): In<egui::Ui>,
+    lanes: Res<StarLanes>,
+    mut lane_settings: ResMut<LaneSettings>,
+) -> egui::Ui {
+    let mut edited = lane_settings.clone();
+    lane_settings_ui(&mut ui, &mut edited, &lanes);
+    lane_settings.set_if_neq(edited);
+    ui
+}
+
+pub fn exploration_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    selected: Res<SelectedStar>,
+    lanes: Res<StarLanes>,
+    explored: Res<ExploredStars>,
+    mut exploration: ResMut<Exploration>,
+    mut ev_fly: EventWriter<FlyToStar>,
+) -> egui::Ui {
+    let mut edited = exploration.clone();
+    let fly_to = exploration_ui(
+        &mut ui,
+        &mut edited,
+        &explored,
+        selected.index,
+        &lanes,
+        || Exploration::start_star(masterok.gen_seed, &masterok.positions, &star_data),
+        |star| StarInfo::new(star, &masterok, &star_data).map_or("?".to_string(), |info| info.name),
+    );
+    exploration.set_if_neq(edited);
+    if let Some(star) = fly_to {
+        ev_fly.send(FlyToStar(star));
+    }
+    ui
+}
+
+pub fn route_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    selected: Res<SelectedStar>,
+    lanes: Res<StarLanes>,
+    route: Res<PlannedRoute>,
+    mut route_request: ResMut<RouteRequest>,
+) -> egui::Ui {
+    let mut edited = route_request.clone();
+    route_ui(
+        &mut ui,
+        &mut edited,
+        &route,
+        selected.index,
+        &masterok,
+        &star_data,
+        lanes.median_length,
+    );
+    route_request.set_if_neq(edited);
+    ui
+}
+
+pub fn overlay_section(In(mut ui): In<egui::Ui>, mut overlay: ResMut<OverlayMode>) -> egui::Ui {
+    let mut edited = *overlay;
+    overlay_mode_ui(&mut ui, &mut edited);
+    overlay.set_if_neq(edited);
+    ui
+}
+
+pub fn factions_section(
+    In(mut ui): In<egui::Ui>,
+    selected: Res<SelectedStar>,
+    territories: Res<Territories>,
+    mut faction_settings: ResMut<FactionSettings>,
+) -> egui::Ui {
+    let mut edited = faction_settings.clone();
+    factions_ui(&mut ui, &mut edited, &territories, selected.index);
+    faction_settings.set_if_neq(edited);
+    ui
+}
+
+//only written when moved, every write replays the history
+pub fn legends_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    legends: Res<Legends>,
+    export: Res<LegendsExport>,
+    mut view: ResMut<LegendsView>,
+    mut ev_export: EventWriter<ExportLegends>,
+    mut ev_fly: EventWriter<FlyToStar>,
+) -> egui::Ui {
+    let mut year = view.year;
+    let action = legends_ui(&mut ui, &legends, &mut year, &export, |star| {
+        StarInfo::new(star, &masterok, &star_data).map_or("?".to_string(), |info| info.name)
+    });
+    if year != view.year {
+        view.year = year;
+    }
+    match action {
+        Some(LegendsAction::Export) => {
+            ev_export.send(ExportLegends);
+        }
+        Some(LegendsAction::FlyTo(star)) => {
+            ev_fly.send(FlyToStar(star));
+        }
+        None => (),
+    }
+    ui
+}
+
+pub fn catalog_section(
+    In(mut ui): In<egui::Ui>,
+    export: Res<CatalogExport>,
+    mut ev_export: EventWriter<ExportCatalog>,
+) -> egui::Ui {
+    if catalog_export_ui(&mut ui, &export) {
+        ev_export.send(ExportCatalog);
+    }
+    ui
+}
+
+pub fn camera_path_section(
+    In(mut ui): In<egui::Ui>,
+    keymap: Res<KeyMap>,
+    mut cinematic: ResMut<Cinematic>,
+    mut camera_path: ResMut<CameraPath>,
+    mut ev_keyframe: EventWriter<AddKeyframe>,
+) -> egui::Ui {
+    let mut edited = camera_path.clone();
+    if camera_path_ui(&mut ui, &mut edited, &mut cinematic, &keymap) {
+        ev_keyframe.send(AddKeyframe);
+    }
+    camera_path.set_if_neq(edited);
+    ui
+}
+
+pub fn bookmarks_section(
+    In(mut ui): In<egui::Ui>,
+    keymap: Res<KeyMap>,
+    mut bookmarks: ResMut<Bookmarks>,
+    mut draft: ResMut<BookmarkDraft>,
+    mut ev_add: EventWriter<AddBookmark>,
+    mut ev_jump: EventWriter<JumpToBookmark>,
+) -> egui::Ui {
+    let mut edited = bookmarks.clone();
+    let action = bookmarks_ui(&mut ui, &mut edited, &mut draft, &keymap);
+    bookmarks.set_if_neq(edited);
+    match action {
+        Some(BookmarkAction::Add) => {
+            ev_add.send(AddBookmark(std::mem::take(&mut draft.name)));
+        }
+        Some(BookmarkAction::Jump(index)) => {
+            ev_jump.send(JumpToBookmark(index));
+        }
+        None => (),
+    }
+    ui
+}
+
+pub fn share_section(
+    In(mut ui): In<egui::Ui>,
+    masterok: Res<Masterik>,
+    star_data: Res<StarData>,
+    bookmarks: Res<Bookmarks>,
+    mut share: ResMut<ShareCodes>,
+    mut ev_open: EventWriter<OpenShareCode>,
+    mut toasts: ResMut<ErrorToasts>,
+) -> egui::Ui {
+    match share_ui(&mut ui, &mut share) {
+        Some(ShareAction::Copy) => {
+            let code = ShareCode::new(
+                masterok.settings(),
+                star_data.clone(),
+                bookmarks.list.clone(),
+            )
+            .encode();
+            match code {
+                Ok(code) => {
+                    ui.output_mut(|output| output.copied_text = code.clone());
+                    share.last_code = Some(code);
+                }
+                Err(err) => toasts.report(&err),
+            }
+        }
+        Some(ShareAction::Open) => {
+            let code = std::mem::take(&mut share.input);
+            ev_open.send(OpenShareCode(code));
+        }
+        None => (),
+    }
+    ui
+}
+
+pub fn saves_section(
+    In(mut ui): In<egui::Ui>,
+    slots: Res<SaveSlots>,
+    mut ev_save: EventWriter<SaveToSlot>,
+    mut ev_load: EventWriter<LoadFromSlot>,
+) -> egui::Ui {
+    match save_slots_ui(&mut ui, &slots) {
+        Some(SlotAction::Save(slot)) => {
+            ev_save.send(SaveToSlot(slot));
+        }
+        Some(SlotAction::Load(slot)) => {
+            ev_load.send(LoadFromSlot(slot));
+        }
+        None => (),
+    }
+    ui
+}
+
+pub fn display_section(In(mut ui): In<egui::Ui>, mut preferences: ResMut<Preferences>) -> egui::Ui {
+    let mut edited = preferences.clone();
+    display_preferences_ui(&mut ui, &mut edited);
+    preferences.set_if_neq(edited);
+    ui
+}
+
+pub fn attract_section(
+    In(mut ui): In<egui::Ui>,
+    mut settings: ResMut<AttractSettings>,
+) -> egui::Ui {
+    let mut edited = settings.clone();
+    attract_settings_ui(&mut ui, &mut edited);
+    settings.set_if_neq(edited);
+    ui
+}
+
+pub fn draw_info_menu(
+    terminal: &mut Terminal<RataguiBackend>,
+    masterok: &Masterik,
+    star_data: &StarData,
+    keymap: &KeyMap,
+    hover: &HoverInfo,
+    fps: f64,
+) -> Result<(), GalaxyError> {
+    terminal
+        .draw(|frame| {
+            //help text on top, the cursor readout pinned to the bottom
+            let areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(5)])
+                .split(frame.size());
+
+            let mut lines = Text::from(vec![
+                Line::from(format!("FPS: {} ", fps as i64)),
+                Line::from(" "),
+                Line::from(format!(
+                    "[{}{}{}{}] - Move Camera ",
+                    keymap.label(Action::MoveUp),
+                    keymap.label(Action::MoveLeft),
+                    keymap.label(Action::MoveDown),
+                    keymap.label(Action::MoveRight)
+                )),
+                Line::from(format!(
+                    "{} - Zoom Out/In",
+                    keymap.pair(Action::ZoomOut, Action::ZoomIn)
+                )),
+                Line::from("[Mouse] - Drag Pan / Scroll Zoom"),
+                Line::from("[Click] - Select Star"),
+                Line::from(format!(
+                    "{} - View System/Back",
+                    keymap.pair(Action::EnterSystem, Action::LeaveSystem)
+                )),
+                Line::from("[Pad] - Stick Pan / Triggers Zoom"),
+                Line::from(format!(
+                    "[{}] - Hide/Show Panel",
+                    keymap.label(Action::TogglePanel)
+                )),
+                Line::from(format!(
+                    "[{}-{}] - Jump to Bookmark",
+                    keymap.label(Action::Bookmark(0)),
+                    keymap.label(Action::Bookmark(BOOKMARK_KEYS - 1))
+                )),
+                Line::from(format!(
+                    "{} - Record/Play Camera Path",
+                    keymap.pair(Action::RecordCameraPath, Action::PlayCameraPath)
+                )),
+                Line::from(" "),
+                Line::from(format!("Seed: {} ", masterok.gen_seed)),
+                Line::from(format!(
+                    "[{}] - Change Seed",
+                    keymap.label(Action::ChangeSeed)
+                )),
+                Line::from(format!(
+                    "[{}] - Default Settings",
+                    keymap.label(Action::ResetToDefault)
+                )),
+                Line::from(format!(
+                    "{} - Undo/Redo",
+                    keymap.pair(Action::Undo, Action::Redo)
+                )),
+                Line::from(" "),
+                Line::from(format!("Stars: {} ", masterok.total_stars + 20000)), //adding 30000 here because I spawn 30000 stars to act as the backdrop of the galaxy
+                Line::from(format!(
+                    "{} - Add/Delete 1000 Stars",
+                    keymap.pair(Action::AddStars1000, Action::RemoveStars1000)
+                )),
+                Line::from(format!(
+                    "{} - Add/Remove 10000 Stars",
+                    keymap.pair(Action::AddStars10000, Action::RemoveStars10000)
+                )),
+                Line::from(" "),
+                Line::from(format!("Spiral Arms: {} ", masterok.spiral_arm_count)),
+                Line::from(format!(
+                    "{} - Add/Remove Spiral Arm",
+                    keymap.pair(Action::AddArm, Action::RemoveArm)
+                )),
+                Line::from(" "),
+                Line::from("Increase / Decrease"),
+                Line::from(" "),
+                Line::from(format!(
+                    "{} Galaxy Angle Mod: {} ",
+                    keymap.pair(Action::AddAngle, Action::RemoveAngle),
+                    masterok.angle_mod
+                )),
+                Line::from(format!(
+                    "{} Galaxy Radius Mod: {} ",
+                    keymap.pair(Action::AddRadius, Action::RemoveRadius),
+                    masterok.radius_mod
+                )),
+                Line::from(format!(
+                    "{} Galaxy Distance Mod: {} ",
+                    keymap.pair(Action::AddDistance, Action::RemoveDistance),
+                    masterok.distance_mod
+                )),
+                Line::from(" "),
+                Line::from("Toggle Star Types in Galaxy"),
+                Line::from(" "),
+            ]);
+
+            for class in StarClass::ALL {
+                lines.lines.push(
+                    Line::from(format!(
+                        "[{}] - {}-Class ({})",
+                        keymap.label(Action::ToggleClass(class)),
+                        class.letter(),
+                        class.description()
+                    ))
+                    .style(if class.enabled(masterok) {
+                        Modifier::empty()
+                    } else {
+                        Modifier::CROSSED_OUT
+                    }),
+                );
+            }
+
+            //the web build has nothing to quit to
+            if QUIT_SUPPORTED {
+                lines.lines.push(Line::from(" "));
+                lines.lines.push(Line::from(format!(
+                    "[{}] - Quit",
+                    keymap.label(Action::Quit)
+                )));
+            }
+
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .on_black()
+                    .block(Block::new().title("Kosmos").gray().borders(Borders::ALL)),
+                areas[0],
+            );
+
+            frame.render_widget(
+                Paragraph::new(cursor_readout(masterok, star_data, hover))
+                    .on_black()
+                    .block(Block::new().title("Cursor").gray().borders(Borders::ALL)),
+                areas[1],
+            );
+        })
+        .map(|_| ())
+        .map_err(GalaxyError::TerminalDraw)
+}
+
+//cartesian and polar position of the cursor relative to the core plus the star under it, if any
+fn cursor_readout(masterok: &Masterik, star_data: &StarData, hover: &HoverInfo) -> Text<'static> {
+    let Some(world) = hover.world else {
+        return Text::from(vec![Line::from("-"), Line::from(" "), Line::from(" ")]);
+    };
+
+    let angle = world.y.atan2(world.x).to_degrees();
+    let nearest = hover
+        .nearest
+        .and_then(|(index, distance)| StarInfo::new(index, masterok, star_data).zip(Some(distance)))
+        .map_or("Nearest: -".to_string(), |(info, distance)| {
+            format!(
+                "Nearest: {} {}-Class ({:.0} away)",
+                info.name,
+                info.class.letter(),
+                distance.max(0.0)
+            )
+        });
+
+    Text::from(vec![
+        Line::from(format!("X: {:.0} Y: {:.0}", world.x, world.y)),
+        Line::from(format!("R: {:.0} Angle: {:.1} deg", world.length(), angle)),
+        Line::from(nearest),
+    ])
+}
+
+//lets the camera and picking systems know when the pointer belongs to egui, they don't depend on egui themselves
+pub fn update_ui_focus(mut contexts: EguiContexts, mut focus: ResMut<UiFocus>) {
+    let ctx = contexts.ctx_mut();
+    focus.pointer_over_ui = ctx.is_pointer_over_area() || ctx.wants_pointer_input();
+    focus.keyboard_over_ui = ctx.wants_keyboard_input();
+}